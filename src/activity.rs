@@ -0,0 +1,62 @@
+//! Active time worked out from when hook events arrive. Events close
+//! together belong to one active segment; a gap longer than the idle
+//! threshold ends it, so time away from the keyboard is not counted.
+
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+
+/// When a hook event arrived, and how long the work it reports took
+/// (a tool's run time, for example), if the payload said.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ActivityEvent {
+    pub event_type: String,
+    pub occurred_at: DateTime<Local>,
+    pub duration_ms: Option<u64>,
+}
+
+/// A stretch of uninterrupted activity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+impl Segment {
+    pub fn duration_ms(&self) -> i64 {
+        (self.end - self.start).num_milliseconds()
+    }
+}
+
+/// Split a session's events into active segments. Each event covers the
+/// span of work it reports, ending when it arrived; spans separated by no
+/// more than `idle_gap` are joined.
+pub fn segments(events: &[ActivityEvent], idle_gap: Duration) -> Vec<Segment> {
+    let mut spans: Vec<Segment> = events
+        .iter()
+        .map(|event| {
+            // A duration too long to subtract is taken as no duration at all
+            let worked = event.duration_ms.unwrap_or(0).min(i64::MAX as u64) as i64;
+            let start = Duration::try_milliseconds(worked)
+                .and_then(|worked| event.occurred_at.checked_sub_signed(worked))
+                .unwrap_or(event.occurred_at);
+            Segment { start, end: event.occurred_at }
+        })
+        .collect();
+    spans.sort_by_key(|span| span.start);
+
+    let mut merged: Vec<Segment> = Vec::new();
+    for span in spans {
+        match merged.last_mut() {
+            Some(current) if span.start - current.end <= idle_gap => {
+                current.end = current.end.max(span.end);
+            }
+            _ => merged.push(span),
+        }
+    }
+    merged
+}
+
+/// Total active time across segments, in milliseconds.
+pub fn active_ms(segments: &[Segment]) -> i64 {
+    segments.iter().map(Segment::duration_ms).sum()
+}
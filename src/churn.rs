@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+
+/// Lines added and removed by a change.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct LineChurn {
+    pub added: u32,
+    pub removed: u32,
+}
+
+impl LineChurn {
+    pub fn total(&self) -> u32 {
+        self.added + self.removed
+    }
+
+    pub fn display(&self) -> String {
+        format!("+{}/-{}", self.added, self.removed)
+    }
+}
+
+impl std::ops::AddAssign for LineChurn {
+    fn add_assign(&mut self, other: Self) {
+        self.added += other.added;
+        self.removed += other.removed;
+    }
+}
+
+// Beyond this many lines on either side the LCS table gets too large for a hook
+const MAX_DIFF_LINES: usize = 2000;
+
+/// Work out the lines a file-editing tool call added and removed from its input.
+pub fn from_tool_input(tool_name: &str, input: &serde_json::Value) -> Option<LineChurn> {
+    let str_field = |value: &serde_json::Value, key: &str| {
+        value.get(key).and_then(|v| v.as_str()).map(|s| s.to_string())
+    };
+
+    match tool_name {
+        "Edit" => {
+            let old = str_field(input, "old_string")?;
+            let new = str_field(input, "new_string")?;
+            Some(diff_lines(&old, &new))
+        }
+        "MultiEdit" => {
+            let mut churn = LineChurn::default();
+            for edit in input.get("edits")?.as_array()? {
+                let old = str_field(edit, "old_string").unwrap_or_default();
+                let new = str_field(edit, "new_string").unwrap_or_default();
+                churn += diff_lines(&old, &new);
+            }
+            Some(churn)
+        }
+        "Write" => {
+            let content = str_field(input, "content")?;
+            Some(LineChurn {
+                added: content.lines().count() as u32,
+                removed: 0,
+            })
+        }
+        "NotebookEdit" => {
+            let source = str_field(input, "new_source").unwrap_or_default();
+            let lines = source.lines().count() as u32;
+            Some(match str_field(input, "edit_mode").as_deref() {
+                Some("delete") => LineChurn { added: 0, removed: lines },
+                _ => LineChurn { added: lines, removed: 0 },
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Count added and removed lines between two texts using a longest common
+/// subsequence over lines, so unchanged context is not counted.
+pub fn diff_lines(old: &str, new: &str) -> LineChurn {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    // Trim the common prefix and suffix before doing any real work
+    let prefix = old_lines
+        .iter()
+        .zip(&new_lines)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old_lines[prefix..old_lines.len() - suffix];
+    let new_mid = &new_lines[prefix..new_lines.len() - suffix];
+
+    if old_mid.len() > MAX_DIFF_LINES || new_mid.len() > MAX_DIFF_LINES {
+        return LineChurn {
+            added: new_mid.len() as u32,
+            removed: old_mid.len() as u32,
+        };
+    }
+
+    let common = lcs_len(old_mid, new_mid);
+    LineChurn {
+        added: (new_mid.len() - common) as u32,
+        removed: (old_mid.len() - common) as u32,
+    }
+}
+
+fn lcs_len(a: &[&str], b: &[&str]) -> usize {
+    let mut previous = vec![0usize; b.len() + 1];
+    let mut current = vec![0usize; b.len() + 1];
+
+    for line_a in a {
+        for (j, line_b) in b.iter().enumerate() {
+            current[j + 1] = if line_a == line_b {
+                previous[j] + 1
+            } else {
+                current[j].max(previous[j + 1])
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// Render the Markdown "Files Modified" section with per-file and total churn.
+pub fn format_section(files: &[(String, LineChurn)]) -> String {
+    let mut content = String::new();
+    if files.is_empty() {
+        return content;
+    }
+
+    let mut total = LineChurn::default();
+    content.push_str("### 📁 **Files Modified**\n");
+    for (file, churn) in files {
+        if churn.total() > 0 {
+            content.push_str(&format!("- {} _({})_\n", file, churn.display()));
+        } else {
+            content.push_str(&format!("- {}\n", file));
+        }
+        total += *churn;
+    }
+    if total.total() > 0 {
+        content.push_str(&format!(
+            "- **Session churn:** {} lines across {} file{}\n",
+            total.display(),
+            files.len(),
+            if files.len() == 1 { "" } else { "s" }
+        ));
+    }
+    content.push('\n');
+    content
+}
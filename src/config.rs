@@ -0,0 +1,146 @@
+use anyhow::{Context, Result};
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Optional user settings, read from `diary-config.json` in the diary directory.
+/// Every field has a default, so the file only needs the settings being changed.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DiaryConfig {
+    /// Patterns used to find ticket and issue references in prompts and branch names
+    pub reference_patterns: Vec<ReferencePattern>,
+    /// Ticket-style keys with these prefixes are not references (e.g. `UTF-8`)
+    pub ignored_reference_prefixes: Vec<String>,
+    /// Tool-to-category rules, tried in order before the built-in ones
+    pub tool_categories: Vec<ToolCategoryRule>,
+    /// A pause between events longer than this ends an active segment
+    pub idle_gap_minutes: u64,
+    /// Rounding and client codes for `timesheet`
+    pub timesheet: TimesheetConfig,
+    /// Where `export vault` writes daily notes
+    pub vault: VaultConfig,
+}
+
+/// A regular expression that identifies one kind of reference. If the pattern
+/// has a capture group named `key`, that group is recorded instead of the
+/// whole match.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReferencePattern {
+    pub kind: String,
+    pub pattern: String,
+}
+
+/// Assigns tools whose name matches a glob pattern (`mcp__linear__*`,
+/// `Notebook*`) to an accomplishment category.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ToolCategoryRule {
+    pub pattern: String,
+    pub category: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct TimesheetConfig {
+    /// Durations are rounded to a multiple of this many minutes
+    pub increment_minutes: u32,
+    /// "nearest", "up" or "down"
+    pub rounding: String,
+    /// Project directories mapped to clients, the longest matching path wins
+    pub clients: Vec<ClientMapping>,
+}
+
+/// Bills work in `path` or any directory under it to a client.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClientMapping {
+    pub path: String,
+    pub client: String,
+    #[serde(default)]
+    pub code: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct VaultConfig {
+    /// The vault directory; `~` is the home directory
+    pub path: Option<String>,
+    /// chrono format for a day's note, relative to the vault
+    pub filename_pattern: String,
+    /// Level of the diary's top heading in the note
+    pub heading_level: usize,
+    /// Update today's note whenever a session ends
+    pub on_session_end: bool,
+}
+
+impl Default for VaultConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            filename_pattern: "%Y-%m-%d.md".to_string(),
+            heading_level: 2,
+            on_session_end: false,
+        }
+    }
+}
+
+impl Default for TimesheetConfig {
+    fn default() -> Self {
+        Self {
+            increment_minutes: 15,
+            rounding: "nearest".to_string(),
+            clients: Vec::new(),
+        }
+    }
+}
+
+impl Default for DiaryConfig {
+    fn default() -> Self {
+        Self {
+            reference_patterns: default_reference_patterns(),
+            ignored_reference_prefixes: ["UTF", "SHA", "ISO", "RFC", "HTTP", "TLS", "SSL", "MD", "X"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            tool_categories: Vec::new(),
+            idle_gap_minutes: 10,
+            timesheet: TimesheetConfig::default(),
+            vault: VaultConfig::default(),
+        }
+    }
+}
+
+fn default_reference_patterns() -> Vec<ReferencePattern> {
+    [
+        ("github", r"https?://github\.com/[\w.-]+/[\w.-]+/(?:issues|pull)/\d+"),
+        ("gitlab", r"https?://gitlab\.[\w.-]+/[\w./-]+/-/(?:issues|merge_requests)/\d+"),
+        ("ticket", r"\b(?P<key>[A-Z][A-Z0-9]+-\d+)\b"),
+        ("issue", r"(?:^|[\s(\[])(?P<key>#\d+)\b"),
+    ]
+    .iter()
+    .map(|(kind, pattern)| ReferencePattern {
+        kind: kind.to_string(),
+        pattern: pattern.to_string(),
+    })
+    .collect()
+}
+
+impl DiaryConfig {
+    pub const FILE_NAME: &'static str = "diary-config.json";
+
+    /// Load the config from `diary_dir`, falling back to defaults if there is none.
+    pub fn load(diary_dir: &Path) -> Result<Self> {
+        let path = diary_dir.join(Self::FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config: {:?}", path))?;
+        serde_json::from_str(&contents).with_context(|| format!("Invalid config: {:?}", path))
+    }
+
+    /// `idle_gap_minutes` as a duration; a gap too long to represent never ends a segment.
+    pub fn idle_gap(&self) -> Duration {
+        Duration::try_minutes(self.idle_gap_minutes.min(i64::MAX as u64) as i64).unwrap_or(Duration::MAX)
+    }
+}
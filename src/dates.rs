@@ -0,0 +1,163 @@
+//! Day ranges for diary queries, from dates or expressions such as `today`,
+//! `this-week`, `last monday` or `7d`. Days are local calendar days.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, Utc, Weekday};
+
+/// An inclusive range of local days; an open end is unbounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DateRange {
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+}
+
+impl DateRange {
+    pub fn day(day: NaiveDate) -> Self {
+        Self { since: Some(day), until: Some(day) }
+    }
+
+    pub fn contains(&self, day: NaiveDate) -> bool {
+        self.since.is_none_or(|since| day >= since) && self.until.is_none_or(|until| day <= until)
+    }
+
+    pub fn is_unbounded(&self) -> bool {
+        self.since.is_none() && self.until.is_none()
+    }
+
+    /// How the range reads in a report heading, e.g. "2026-10-12 to 2026-10-18".
+    pub fn describe(&self) -> String {
+        match (self.since, self.until) {
+            (Some(since), Some(until)) if since == until => since.to_string(),
+            (Some(since), Some(until)) => format!("{} to {}", since, until),
+            (Some(since), None) => format!("since {}", since),
+            (None, Some(until)) => format!("until {}", until),
+            (None, None) => "all time".to_string(),
+        }
+    }
+}
+
+/// Parse a period relative to `today`:
+///
+/// - `today`, `yesterday`
+/// - `this-week`, `last-week` (weeks start on Monday), `this-month`, `last-month`
+/// - `monday` or `last monday`: the most recent Monday before today
+/// - `7d`, `2w`: that many days or weeks up to and including today
+/// - `2026-10-15`: a single day
+pub fn parse_range(expression: &str, today: NaiveDate) -> Result<DateRange> {
+    let normalized = expression.trim().to_lowercase().replace(['_', ' '], "-");
+
+    let range = match normalized.as_str() {
+        "today" => DateRange::day(today),
+        "yesterday" => DateRange::day(today - Duration::days(1)),
+        "this-week" | "week" => DateRange { since: Some(week_start(today)), until: Some(today) },
+        "last-week" => {
+            let start = week_start(today) - Duration::days(7);
+            DateRange { since: Some(start), until: Some(start + Duration::days(6)) }
+        }
+        "this-month" | "month" => DateRange { since: Some(today.with_day(1).unwrap_or(today)), until: Some(today) },
+        "last-month" => {
+            let end = today.with_day(1).unwrap_or(today) - Duration::days(1);
+            DateRange { since: Some(end.with_day(1).unwrap_or(end)), until: Some(end) }
+        }
+        other => {
+            if let Ok(weekday) = other.strip_prefix("last-").unwrap_or(other).parse::<Weekday>() {
+                DateRange::day(previous_weekday(today, weekday))
+            } else if let Some(since) = span_days(&other.replace('-', ""))
+                .and_then(|days| Duration::try_days(days - 1))
+                .and_then(|span| today.checked_sub_signed(span))
+            {
+                DateRange { since: Some(since), until: Some(today) }
+            } else if let Ok(day) = NaiveDate::parse_from_str(other, "%Y-%m-%d") {
+                DateRange::day(day)
+            } else {
+                bail!(
+                    "Unrecognised date '{}': use a date (2026-10-15), today, yesterday, this-week, last-week, this-month, last-month, a weekday (last monday) or a span (7d, 2w)",
+                    expression
+                );
+            }
+        }
+    };
+    Ok(range)
+}
+
+/// The range a command was asked for: a period expression, or `--since` and
+/// `--until`, or `default` when none was given.
+pub fn resolve(
+    period: Option<&str>,
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    default: DateRange,
+    today: NaiveDate,
+) -> Result<DateRange> {
+    match period {
+        Some(period) => parse_range(period, today),
+        None if since.is_some() || until.is_some() => Ok(DateRange { since, until }),
+        None => Ok(default),
+    }
+}
+
+/// A single day for `--since`, `--until` or `--date`. Expressions that cover
+/// several days resolve to their first day, so `--since this-week` means Monday.
+pub fn parse_day(expression: &str, today: NaiveDate) -> Result<NaiveDate> {
+    let range = parse_range(expression, today)?;
+    Ok(range.since.or(range.until).unwrap_or(today))
+}
+
+/// clap value parser for day arguments, relative to the local date.
+pub fn parse_day_arg(expression: &str) -> Result<NaiveDate, String> {
+    parse_day(expression, Local::now().date_naive()).map_err(|e| e.to_string())
+}
+
+fn week_start(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+/// The Monday-to-Sunday week containing `day`.
+pub fn week_of(day: NaiveDate) -> DateRange {
+    let start = week_start(day);
+    DateRange { since: Some(start), until: Some(start + Duration::days(6)) }
+}
+
+/// The calendar month containing `day`.
+pub fn month_of(day: NaiveDate) -> DateRange {
+    let start = day.with_day(1).unwrap_or(day);
+    let next = if start.month() == 12 {
+        NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+    };
+    DateRange { since: Some(start), until: next.map(|next| next - Duration::days(1)) }
+}
+
+/// The latest `weekday` strictly before `today`, so on a Monday "monday" is a week ago.
+fn previous_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let back = (today.weekday().num_days_from_monday() as i64 - weekday.num_days_from_monday() as i64 + 6) % 7 + 1;
+    today - Duration::days(back)
+}
+
+/// `7d` → 7, `2w` → 14. `None` for spans too large to count.
+fn span_days(expression: &str) -> Option<i64> {
+    let (number, unit) = expression.split_at(expression.find(|c: char| !c.is_ascii_digit())?);
+    let count: i64 = number.parse().ok().filter(|n| *n > 0)?;
+    match unit {
+        "d" | "day" | "days" => Some(count),
+        "w" | "week" | "weeks" => count.checked_mul(7),
+        _ => None,
+    }
+}
+
+/// A stored timestamp in local time: RFC 3339, or SQLite's
+/// `CURRENT_TIMESTAMP` format, which is in UTC.
+pub fn local_time(timestamp: &str) -> Option<DateTime<Local>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(timestamp) {
+        return Some(time.with_timezone(&Local));
+    }
+    NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|time| DateTime::<Utc>::from_naive_utc_and_offset(time, Utc).with_timezone(&Local))
+}
+
+/// The local day of a stored timestamp.
+pub fn local_day(timestamp: &str) -> Option<NaiveDate> {
+    local_time(timestamp).map(|time| time.date_naive())
+}
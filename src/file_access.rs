@@ -0,0 +1,179 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+pub const READ: &str = "read";
+pub const CREATED: &str = "created";
+pub const EDITED: &str = "edited";
+pub const DELETED: &str = "deleted";
+pub const SEARCHED: &str = "searched";
+
+/// One touch of a file or path by a tool call. Searches record the pattern or
+/// directory that was searched rather than a single file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FileAccess {
+    pub path: String,
+    pub access_type: String,
+    pub tool_name: String,
+    pub accessed_at: DateTime<Local>,
+}
+
+impl FileAccess {
+    /// Whether the access changed the file, as opposed to looking at it.
+    pub fn is_modification(&self) -> bool {
+        is_modification(&self.access_type)
+    }
+}
+
+pub fn is_modification(access_type: &str) -> bool {
+    matches!(access_type, CREATED | EDITED | DELETED)
+}
+
+/// Work out which paths a tool call touched and how. `response` is the raw
+/// `tool_response`, used to tell a `Write` that created a file from one that
+/// overwrote it.
+pub fn from_tool_call(
+    tool_name: &str,
+    input: &serde_json::Value,
+    response: Option<&serde_json::Value>,
+) -> Vec<(String, &'static str)> {
+    let str_field = |key: &str| input.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+
+    match tool_name {
+        "Read" | "NotebookRead" => str_field("file_path")
+            .or_else(|| str_field("notebook_path"))
+            .map(|path| vec![(path, READ)])
+            .unwrap_or_default(),
+        "Edit" | "MultiEdit" => str_field("file_path")
+            .map(|path| vec![(path, EDITED)])
+            .unwrap_or_default(),
+        "NotebookEdit" => str_field("notebook_path")
+            .or_else(|| str_field("file_path"))
+            .map(|path| vec![(path, EDITED)])
+            .unwrap_or_default(),
+        "Write" => {
+            let created = response
+                .and_then(|r| r.get("type"))
+                .and_then(|t| t.as_str())
+                .map(|t| t == "create")
+                .unwrap_or(false);
+            str_field("file_path")
+                .map(|path| vec![(path, if created { CREATED } else { EDITED })])
+                .unwrap_or_default()
+        }
+        "Glob" => str_field("pattern")
+            .map(|pattern| match str_field("path") {
+                Some(dir) => format!("{}/{}", dir.trim_end_matches('/'), pattern),
+                None => pattern,
+            })
+            .map(|path| vec![(path, SEARCHED)])
+            .unwrap_or_default(),
+        "Grep" | "LS" => {
+            let path = str_field("path").unwrap_or_else(|| ".".to_string());
+            match str_field("glob") {
+                Some(glob) => vec![(format!("{}/{}", path.trim_end_matches('/'), glob), SEARCHED)],
+                None => vec![(path, SEARCHED)],
+            }
+        }
+        "Bash" => str_field("command")
+            .map(|command| deleted_by_command(&command))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|path| (path, DELETED))
+            .collect(),
+        // Anything else with a file_path parameter is assumed to have looked at it
+        _ => str_field("file_path")
+            .map(|path| vec![(path, READ)])
+            .unwrap_or_default(),
+    }
+}
+
+/// Files removed by a simple `rm` or `git rm` command. Anything more involved
+/// than a plain list of arguments is left alone rather than guessed at.
+fn deleted_by_command(command: &str) -> Vec<String> {
+    let mut deleted = Vec::new();
+    for part in command.split(['&', ';', '|', '\n']) {
+        let words: Vec<&str> = part.split_whitespace().collect();
+        let args = match words.as_slice() {
+            ["rm", rest @ ..] => rest,
+            ["git", "rm", rest @ ..] => rest,
+            _ => continue,
+        };
+        if args.iter().any(|arg| arg.contains(['*', '?', '$', '`', '>', '<'])) {
+            continue;
+        }
+        deleted.extend(
+            args.iter()
+                .filter(|arg| !arg.starts_with('-'))
+                .map(|arg| arg.trim_matches(['"', '\'']).to_string()),
+        );
+    }
+    deleted
+}
+
+/// Render the Markdown "Files Explored" section: paths that were read or
+/// searched but not changed, with how often each was looked at.
+pub fn format_section(accesses: &[(String, String, u32)]) -> String {
+    let mut content = String::new();
+    if accesses.is_empty() {
+        return content;
+    }
+
+    content.push_str("### 🔍 **Files Explored**\n");
+    for (path, access_type, count) in accesses {
+        let times = if *count == 1 { String::new() } else { format!(" ×{}", count) };
+        content.push_str(&format!("- {} _({}{})_\n", path, access_type, times));
+    }
+    content.push('\n');
+    content
+}
+
+/// Collapse an access log into the explored-only view used by `format_section`:
+/// read and searched paths with counts, minus anything that was also modified.
+pub fn explored(accesses: &[FileAccess]) -> Vec<(String, String, u32)> {
+    let modified: Vec<&str> = accesses
+        .iter()
+        .filter(|a| a.is_modification())
+        .map(|a| a.path.as_str())
+        .collect();
+
+    let mut explored: Vec<(String, String, u32)> = Vec::new();
+    for access in accesses.iter().filter(|a| !a.is_modification()) {
+        if modified.contains(&access.path.as_str()) {
+            continue;
+        }
+        match explored
+            .iter_mut()
+            .find(|(path, kind, _)| *path == access.path && *kind == access.access_type)
+        {
+            Some((_, _, count)) => *count += 1,
+            None => explored.push((access.path.clone(), access.access_type.clone(), 1)),
+        }
+    }
+    explored.sort();
+    explored
+}
+
+/// Count distinct files by what happened to them over a session, as
+/// (edited, created, deleted, explored). A file created and then edited
+/// counts as created; one edited and then deleted counts as deleted.
+pub fn count_changes(accesses: &[FileAccess]) -> (usize, usize, usize, usize) {
+    let mut outcomes: Vec<(&str, &str)> = Vec::new();
+    for access in accesses {
+        let outcome = match outcomes.iter_mut().find(|(path, _)| *path == access.path) {
+            Some((_, outcome)) => outcome,
+            None => {
+                outcomes.push((access.path.as_str(), access.access_type.as_str()));
+                continue;
+            }
+        };
+        *outcome = match (*outcome, access.access_type.as_str()) {
+            (CREATED, EDITED) => CREATED,
+            (_, READ) | (_, SEARCHED) if is_modification(outcome) => outcome,
+            (_, next) => next,
+        };
+    }
+
+    let count = |kind: &str| outcomes.iter().filter(|(_, outcome)| *outcome == kind).count();
+    let explored = outcomes.iter().filter(|(_, outcome)| *outcome == READ).count();
+    (count(EDITED), count(CREATED), count(DELETED), explored)
+}
@@ -0,0 +1,152 @@
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Extension-less file names that are still clearly files.
+const SPECIAL_FILE_NAMES: &[&str] = &[
+    "Dockerfile", "Containerfile", "Makefile", "GNUmakefile", "Justfile", "Rakefile",
+    "Gemfile", "Procfile", "Vagrantfile", "Jenkinsfile", "Brewfile", "Caddyfile",
+    "Tiltfile", "LICENSE", "README", "CHANGELOG", "CODEOWNERS",
+];
+
+/// Things that look like `name.ext` in prose but are not files.
+const NOT_FILES: &[&str] = &["e.g", "i.e", "etc", "vs", "a.m", "p.m", "approx", "ca"];
+
+/// Top-level domains, so that `example.com` is not taken for a file.
+const DOMAIN_SUFFIXES: &[&str] = &["com", "org", "net", "edu", "gov", "io", "ai", "co", "uk", "de"];
+
+/// Find file references in a prompt: backtick- or quote-delimited paths
+/// (which may contain spaces), `@file` mentions, and bare paths with an
+/// extension or a well-known extension-less name such as `Dockerfile`.
+pub fn extract(prompt: &str) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+    let mut push = |candidate: &str, quoted: bool| {
+        let candidate = trim_candidate(candidate);
+        if looks_like_file(candidate, quoted) && !files.iter().any(|f| f == candidate) {
+            files.push(candidate.to_string());
+        }
+    };
+
+    // Quoted spans are taken whole so paths with spaces survive, then blanked
+    // out so their pieces are not picked up again as bare tokens
+    let quoted = Regex::new(r#"`([^`\n]+)`|"([^"\n]+)"|'([^'\n]+)'"#).expect("valid regex");
+    let mut unquoted = prompt.to_string();
+    for caps in quoted.captures_iter(prompt) {
+        if let Some(inner) = caps.get(1).or_else(|| caps.get(2)).or_else(|| caps.get(3)) {
+            if looks_like_file(trim_candidate(inner.as_str()), true) {
+                push(inner.as_str(), true);
+                unquoted.replace_range(inner.range(), &" ".repeat(inner.len()));
+            }
+        }
+    }
+    let prompt = unquoted.as_str();
+
+    // @src/lib.rs style mentions
+    let mentions = Regex::new(r"(?:^|\s)@([^\s`'\x22]+)").expect("valid regex");
+    for caps in mentions.captures_iter(prompt) {
+        push(&caps[1], false);
+    }
+
+    // Bare tokens, delimited by whitespace and characters that cannot be part of a path
+    let bare = Regex::new(r#"[^\s`"'()\[\]{}<>,;|]+"#).expect("valid regex");
+    for token in bare.find_iter(prompt) {
+        let text = token.as_str();
+        // `config.load()` is a method call, not a file
+        let is_call = prompt[token.end()..].starts_with('(');
+        if is_call || text.contains("://") || text.starts_with('@') {
+            continue;
+        }
+        push(text, false);
+    }
+
+    files
+}
+
+fn trim_candidate(candidate: &str) -> &str {
+    candidate
+        .trim()
+        .trim_end_matches(['.', ',', ':', ';', '!', '?', ')'])
+        .trim_start_matches('(')
+}
+
+fn looks_like_file(candidate: &str, quoted: bool) -> bool {
+    if candidate.is_empty() || candidate.len() > 260 || candidate.contains("://") {
+        return false;
+    }
+    // e-mail addresses and other text with spaces but no path structure
+    if candidate.contains('@') && !candidate.starts_with('@') {
+        return false;
+    }
+    // Only a quoted path may contain spaces, and then only in its name
+    if candidate.contains(' ') && (!quoted || candidate.split(['/', '\\']).any(|part| part.trim() != part)) {
+        return false;
+    }
+
+    let name = candidate
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(candidate);
+
+    if SPECIAL_FILE_NAMES.iter().any(|special| {
+        name == *special || name.starts_with(&format!("{}.", special))
+    }) {
+        return true;
+    }
+
+    // Dotfiles such as .gitignore or .env.local
+    if let Some(rest) = name.strip_prefix('.') {
+        return !rest.is_empty()
+            && rest.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+            && rest.chars().all(|c| c.is_ascii_alphanumeric() || "._-".contains(c));
+    }
+
+    let Some((stem, extension)) = name.rsplit_once('.') else {
+        return false;
+    };
+    if stem.is_empty() || extension.is_empty() || extension.len() > 12 {
+        return false;
+    }
+    // Needs a letter, so version numbers like 1.5 or v2.0 are not files
+    if !extension.chars().all(|c| c.is_ascii_alphanumeric()) || !extension.chars().any(|c| c.is_ascii_alphabetic()) {
+        return false;
+    }
+    if !stem.chars().all(|c| c.is_alphanumeric() || "._-+@ ".contains(c)) {
+        return false;
+    }
+    if NOT_FILES.iter().any(|n| name.eq_ignore_ascii_case(n) || name.eq_ignore_ascii_case(&format!("{}.", n))) {
+        return false;
+    }
+    let is_bare_name = !candidate.contains('/');
+    if is_bare_name && DOMAIN_SUFFIXES.contains(&extension.to_ascii_lowercase().as_str()) {
+        return false;
+    }
+    // Sentences like "Fix it.Then" are not file names
+    if is_bare_name && extension.chars().next().is_some_and(|c| c.is_ascii_uppercase()) && extension.len() > 3 {
+        return false;
+    }
+
+    true
+}
+
+/// Resolve a referenced path against the session's working directory and
+/// report whether it exists there. Existence is unknown (`None`) for a
+/// relative path when there is no working directory to resolve it against.
+pub fn resolve(path: &str, cwd: Option<&Path>) -> (String, Option<bool>) {
+    let expanded = match path.strip_prefix("~/") {
+        Some(rest) => match dirs::home_dir() {
+            Some(home) => home.join(rest),
+            None => return (path.to_string(), None),
+        },
+        None => PathBuf::from(path),
+    };
+
+    let resolved = if expanded.is_absolute() {
+        expanded
+    } else if let Some(cwd) = cwd {
+        cwd.join(expanded)
+    } else {
+        return (path.to_string(), None);
+    };
+
+    let exists = resolved.exists();
+    (resolved.to_string_lossy().to_string(), Some(exists))
+}
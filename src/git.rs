@@ -0,0 +1,164 @@
+//! Git snapshots of the working repository and the commits made during a session.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+
+/// Repository state at a point in the session.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GitSnapshot {
+    pub head: Option<String>,
+    pub branch: Option<String>,
+    pub dirty: bool,
+    pub taken_at: DateTime<Local>,
+}
+
+/// A commit created while the session was running.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GitCommit {
+    pub hash: String,
+    pub subject: String,
+    pub files_changed: u32,
+    pub insertions: u32,
+    pub deletions: u32,
+    pub committed_at: DateTime<Local>,
+}
+
+impl GitCommit {
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(7)]
+    }
+
+    pub fn diffstat(&self) -> String {
+        format!(
+            "{} file{}, +{}/-{}",
+            self.files_changed,
+            if self.files_changed == 1 { "" } else { "s" },
+            self.insertions,
+            self.deletions
+        )
+    }
+}
+
+fn git(cwd: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(cwd)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+}
+
+/// Capture HEAD, branch and dirty state, or `None` if `cwd` is not in a repository.
+pub fn snapshot(cwd: &Path) -> Option<GitSnapshot> {
+    git(cwd, &["rev-parse", "--is-inside-work-tree"])?;
+
+    // A repository without commits has no HEAD yet
+    let head = git(cwd, &["rev-parse", "HEAD"]);
+    let branch = git(cwd, &["symbolic-ref", "--short", "-q", "HEAD"]).filter(|b| !b.is_empty());
+    let dirty = git(cwd, &["status", "--porcelain"])
+        .map(|status| !status.is_empty())
+        .unwrap_or(false);
+
+    Some(GitSnapshot {
+        head,
+        branch,
+        dirty,
+        taken_at: Local::now(),
+    })
+}
+
+/// Commits reachable from HEAD but not from `base`, committed at or after `since`.
+pub fn commits_since(cwd: &Path, base: Option<&str>, since: DateTime<Local>) -> Vec<GitCommit> {
+    let range = match base {
+        Some(base) => format!("{}..HEAD", base),
+        None => "HEAD".to_string(),
+    };
+    let since_arg = format!("--since={}", since.to_rfc3339());
+
+    let Some(log) = git(
+        cwd,
+        &["log", "--format=%x1e%H%x1f%s%x1f%cI", "--shortstat", &since_arg, &range],
+    ) else {
+        return Vec::new();
+    };
+
+    log.split('\x1e')
+        .filter(|record| !record.trim().is_empty())
+        .filter_map(parse_log_record)
+        .collect()
+}
+
+fn parse_log_record(record: &str) -> Option<GitCommit> {
+    let mut lines = record.lines();
+    let mut fields = lines.next()?.split('\x1f');
+    let hash = fields.next()?.to_string();
+    let subject = fields.next()?.to_string();
+    let committed_at = DateTime::parse_from_rfc3339(fields.next()?)
+        .ok()?
+        .with_timezone(&Local);
+
+    let mut commit = GitCommit {
+        hash,
+        subject,
+        files_changed: 0,
+        insertions: 0,
+        deletions: 0,
+        committed_at,
+    };
+
+    // e.g. " 3 files changed, 10 insertions(+), 2 deletions(-)"
+    if let Some(stat) = lines.map(str::trim).find(|line| line.contains("changed")) {
+        for part in stat.split(',') {
+            let part = part.trim();
+            let count = part
+                .split_whitespace()
+                .next()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(0);
+            if part.contains("changed") {
+                commit.files_changed = count;
+            } else if part.contains("insertion") {
+                commit.insertions = count;
+            } else if part.contains("deletion") {
+                commit.deletions = count;
+            }
+        }
+    }
+
+    Some(commit)
+}
+
+/// Render the Markdown "Git Activity" section shared by the live and stored reports.
+pub fn format_section(latest: Option<&GitSnapshot>, commits: &[GitCommit]) -> String {
+    let mut content = String::new();
+    if latest.is_none() && commits.is_empty() {
+        return content;
+    }
+
+    content.push_str("### 🔀 **Git Activity**\n");
+    if let Some(snapshot) = latest {
+        let head = snapshot.head.as_deref().map(|h| &h[..h.len().min(7)]).unwrap_or("(no commits)");
+        content.push_str(&format!(
+            "- Branch: {} @ {}{}\n",
+            snapshot.branch.as_deref().unwrap_or("(detached)"),
+            head,
+            if snapshot.dirty { " (uncommitted changes)" } else { "" }
+        ));
+    }
+    for commit in commits {
+        content.push_str(&format!(
+            "- `{}` {} _({})_\n",
+            commit.short_hash(),
+            commit.subject,
+            commit.diffstat()
+        ));
+    }
+    content.push('\n');
+    content
+}
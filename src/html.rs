@@ -0,0 +1,228 @@
+//! A weekly or monthly report as one HTML file, with inline CSS and SVG
+//! charts and no external assets, for sharing with people who don't use
+//! the diary.
+
+use crate::activity::Segment;
+use crate::dates::DateRange;
+use crate::output::SessionRecord;
+use crate::period::PeriodStats;
+use crate::{format_active_time, format_duration, project_name};
+use chrono::{NaiveDate, Timelike};
+
+const PALETTE: [&str; 8] = ["#4e79a7", "#f28e2b", "#59a14f", "#e15759", "#76b7b2", "#edc948", "#b07aa1", "#9c755f"];
+
+const STYLE: &str = "body{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;max-width:960px;margin:2em auto;padding:0 1em;color:#222}\
+h1{margin-bottom:0}.range{color:#666;margin-top:.2em}.totals{display:flex;flex-wrap:wrap;gap:1em;margin:1.5em 0}\
+.total{background:#f4f6f8;border-radius:6px;padding:.6em 1em}.total b{display:block;font-size:1.4em}\
+.charts{display:flex;flex-wrap:wrap;gap:2em}.chart{flex:1 1 280px}\
+details{border:1px solid #ddd;border-radius:6px;margin:.5em 0;padding:.4em .8em}summary{cursor:pointer;font-weight:600}\
+.muted{color:#666}.legend span{margin-right:1em}.legend i{display:inline-block;width:10px;height:10px;margin-right:4px;border-radius:2px}svg text{font-size:11px;fill:#333}";
+
+/// One session's place on the timeline.
+pub struct TimelineSession {
+    pub project: String,
+    pub segments: Vec<Segment>,
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// A horizontal bar chart; `label` formats each value.
+pub fn bar_chart(title: &str, bars: &[(String, i64)], label: fn(i64) -> String) -> String {
+    let mut content = format!("<div class=\"chart\"><h3>{}</h3>", escape(title));
+    if bars.is_empty() {
+        content.push_str("<p class=\"muted\">Nothing recorded</p></div>");
+        return content;
+    }
+    let max = bars.iter().map(|(_, value)| *value).max().unwrap_or(1).max(1);
+    let row = 22;
+    content.push_str(&format!(
+        "<svg width=\"100%\" viewBox=\"0 0 420 {}\" role=\"img\" aria-label=\"{}\">",
+        bars.len() * row,
+        escape(title)
+    ));
+    for (i, (name, value)) in bars.iter().enumerate() {
+        let y = i * row;
+        let width = (*value as f64 / max as f64 * 200.0).max(1.0);
+        content.push_str(&format!(
+            "<text x=\"0\" y=\"{}\">{}</text><rect x=\"140\" y=\"{}\" width=\"{:.1}\" height=\"16\" fill=\"{}\"><title>{}: {}</title></rect><text x=\"{:.1}\" y=\"{}\">{}</text>",
+            y + 13,
+            escape(&crate::text::ellipsize(name, 22)),
+            y + 2,
+            width,
+            PALETTE[i % PALETTE.len()],
+            escape(name),
+            label(*value),
+            146.0 + width,
+            y + 13,
+            label(*value)
+        ));
+    }
+    content.push_str("</svg></div>");
+    content
+}
+
+/// One row per day, hours across; each active segment is a block coloured
+/// by project.
+pub fn timeline(range: DateRange, sessions: &[TimelineSession]) -> String {
+    let days: Vec<NaiveDate> = match (range.since, range.until) {
+        (Some(since), Some(until)) => since.iter_days().take_while(|day| *day <= until).collect(),
+        _ => Vec::new(),
+    };
+    let mut projects: Vec<&str> = Vec::new();
+    for session in sessions {
+        if !projects.contains(&session.project.as_str()) {
+            projects.push(&session.project);
+        }
+    }
+
+    let (left, hour, row) = (90.0, 35.0, 20);
+    let mut content = format!(
+        "<svg width=\"100%\" viewBox=\"0 0 {} {}\" role=\"img\" aria-label=\"Timeline of sessions\">",
+        left + 24.0 * hour,
+        days.len() * row + 20
+    );
+    for h in (0..=24).step_by(3) {
+        let x = left + h as f64 * hour;
+        content.push_str(&format!(
+            "<line x1=\"{x:.0}\" y1=\"0\" x2=\"{x:.0}\" y2=\"{}\" stroke=\"#e5e5e5\"/><text x=\"{:.0}\" y=\"{}\">{:02}:00</text>",
+            days.len() * row,
+            x - 14.0,
+            days.len() * row + 14,
+            h
+        ));
+    }
+    for (i, day) in days.iter().enumerate() {
+        let y = i * row;
+        content.push_str(&format!("<text x=\"0\" y=\"{}\">{}</text>", y + 14, day.format("%a %m-%d")));
+        for session in sessions {
+            let colour = PALETTE[projects.iter().position(|p| *p == session.project).unwrap_or(0) % PALETTE.len()];
+            for segment in session.segments.iter().filter(|segment| segment.start.date_naive() == *day) {
+                let hours = |time: chrono::DateTime<chrono::Local>| time.num_seconds_from_midnight() as f64 / 3600.0;
+                // Segments past midnight are cut off at the end of the row
+                let end = if segment.end.date_naive() == *day { hours(segment.end) } else { 24.0 };
+                let width = ((end - hours(segment.start)) * hour).max(2.0);
+                content.push_str(&format!(
+                    "<rect x=\"{:.1}\" y=\"{}\" width=\"{:.1}\" height=\"14\" rx=\"2\" fill=\"{}\"><title>{} {}–{}</title></rect>",
+                    left + hours(segment.start) * hour,
+                    y + 3,
+                    width,
+                    colour,
+                    escape(&session.project),
+                    segment.start.format("%H:%M"),
+                    segment.end.format("%H:%M")
+                ));
+            }
+        }
+    }
+    content.push_str("</svg><p class=\"legend\">");
+    for (i, project) in projects.iter().enumerate() {
+        content.push_str(&format!(
+            "<span><i style=\"background:{}\"></i>{}</span>",
+            PALETTE[i % PALETTE.len()],
+            escape(project)
+        ));
+    }
+    content.push_str("</p>");
+    content
+}
+
+fn session_details(session: &SessionRecord) -> String {
+    let start = crate::dates::local_time(&session.start_time)
+        .map(|time| time.format("%a %Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| session.start_time.clone());
+    let project = project_name(session.cwd.as_deref()).unwrap_or_else(|| "Other".to_string());
+    let mut content = format!(
+        "<details><summary>{} · {} · {}</summary>",
+        escape(&start),
+        escape(&project),
+        escape(&format_active_time(session.active_ms, session.active_segments))
+    );
+    if let Some(narrative) = &session.narrative {
+        content.push_str(&format!("<p>{}</p>", escape(narrative)));
+    }
+    let mut push_list = |title: &str, items: Vec<String>| {
+        if items.is_empty() {
+            return;
+        }
+        content.push_str(&format!("<h4>{}</h4><ul>", title));
+        for item in items {
+            content.push_str(&format!("<li>{}</li>", escape(&item)));
+        }
+        content.push_str("</ul>");
+    };
+    push_list("Objectives", session.objectives.clone());
+    push_list(
+        "Accomplishments",
+        session.accomplishments.iter().map(|a| format!("{}: {}", a.category, a.description)).collect(),
+    );
+    push_list("Issues", session.issues.iter().map(|issue| issue.issue.clone()).collect());
+    push_list(
+        "Files modified",
+        session
+            .files_modified
+            .iter()
+            .map(|file| format!("{} (+{} -{})", file.path, file.lines_added, file.lines_removed))
+            .collect(),
+    );
+    push_list(
+        "Tools",
+        session.tool_usage.iter().map(|tool| format!("{} ×{}", tool.tool_name, tool.uses)).collect(),
+    );
+    content.push_str("</details>");
+    content
+}
+
+/// The whole report as one HTML document.
+pub fn render(
+    label: &str,
+    stats: &PeriodStats,
+    timeline_sessions: &[TimelineSession],
+    tools: &[(String, i64)],
+    sessions: &[SessionRecord],
+) -> String {
+    let range = DateRange { since: stats.since, until: stats.until };
+    let title = format!("{} report: {}", label, range.describe());
+    let mut content = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\"><head><meta charset=\"utf-8\"><title>{}</title><style>{}</style></head><body>",
+        escape(&title),
+        STYLE
+    );
+    content.push_str(&format!("<h1>{} report</h1><p class=\"range\">{}</p>", label, escape(&range.describe())));
+
+    content.push_str("<div class=\"totals\">");
+    let totals = [
+        ("Active time", format_duration(stats.active_ms)),
+        ("Sessions", stats.sessions.to_string()),
+        ("Commits", stats.commits.to_string()),
+        ("Test runs", format!("{} ({} green)", stats.test_runs, stats.green_test_runs)),
+    ];
+    for (name, value) in totals {
+        content.push_str(&format!("<div class=\"total\"><b>{}</b>{}</div>", escape(&value), name));
+    }
+    content.push_str("</div>");
+
+    content.push_str("<h2>Timeline</h2>");
+    content.push_str(&timeline(range, timeline_sessions));
+
+    let times = |list: &[crate::period::NamedTime]| -> Vec<(String, i64)> {
+        list.iter().map(|entry| (entry.name.clone(), entry.active_ms)).collect()
+    };
+    content.push_str("<h2>Where the time went</h2><div class=\"charts\">");
+    content.push_str(&bar_chart("Time by category", &times(&stats.categories), format_duration));
+    content.push_str(&bar_chart("Time by project", &times(&stats.projects), format_duration));
+    content.push_str("</div><h2>Tool usage</h2><div class=\"charts\">");
+    content.push_str(&bar_chart("Calls per tool", tools, |uses| uses.to_string()));
+    content.push_str("</div>");
+
+    content.push_str("<h2>Sessions</h2>");
+    if sessions.is_empty() {
+        content.push_str("<p class=\"muted\">No sessions in this period</p>");
+    }
+    for session in sessions {
+        content.push_str(&session_details(session));
+    }
+    content.push_str("</body></html>\n");
+    content
+}
@@ -0,0 +1,97 @@
+//! iCalendar export: each active segment of a session becomes a calendar
+//! event, so work blocks show up next to meetings.
+
+use crate::activity::Segment;
+use chrono::{DateTime, Local, Utc};
+
+/// One VEVENT.
+#[derive(Debug, Clone)]
+pub struct CalendarEvent {
+    /// Stays the same across exports, so re-importing updates the event
+    pub uid: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub summary: String,
+    pub description: String,
+    /// Absolute paths of the files modified during the segment
+    pub attachments: Vec<String>,
+}
+
+/// The UID of a segment: the session it belongs to and when it started.
+pub fn uid(session_key: &str, segment: &Segment) -> String {
+    format!("{}-{}@claude-diary-hook", session_key, segment.start.timestamp())
+}
+
+/// The segment something recorded at `time` belongs to: the last one that
+/// started at or before it, or the first if it came earlier.
+pub fn segment_for(segments: &[Segment], time: DateTime<Local>) -> usize {
+    segments.iter().rposition(|segment| segment.start <= time).unwrap_or(0)
+}
+
+fn timestamp(time: DateTime<Local>) -> String {
+    time.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape a TEXT value (RFC 5545 section 3.3.11).
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// A `file://` URI, percent-encoding everything but unreserved characters and `/`.
+fn file_uri(path: &str) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+/// Fold a content line to 75 octets, as the format requires, without
+/// splitting a character.
+fn push_line(content: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            content.push_str("\r\n ");
+            width = 1;
+        }
+        content.push(c);
+        width += c.len_utf8();
+    }
+    content.push_str("\r\n");
+}
+
+/// Render events as an iCalendar document.
+pub fn to_ics(events: &[CalendarEvent]) -> String {
+    let stamp = timestamp(Local::now());
+    let mut content = String::new();
+    push_line(&mut content, "BEGIN:VCALENDAR");
+    push_line(&mut content, "VERSION:2.0");
+    push_line(&mut content, "PRODID:-//claude-diary-hook//Work Sessions//EN");
+    push_line(&mut content, "CALSCALE:GREGORIAN");
+    for event in events {
+        push_line(&mut content, "BEGIN:VEVENT");
+        push_line(&mut content, &format!("UID:{}", event.uid));
+        push_line(&mut content, &format!("DTSTAMP:{}", stamp));
+        push_line(&mut content, &format!("DTSTART:{}", timestamp(event.start)));
+        push_line(&mut content, &format!("DTEND:{}", timestamp(event.end)));
+        push_line(&mut content, &format!("SUMMARY:{}", escape(&event.summary)));
+        if !event.description.is_empty() {
+            push_line(&mut content, &format!("DESCRIPTION:{}", escape(&event.description)));
+        }
+        for path in &event.attachments {
+            push_line(&mut content, &format!("ATTACH:{}", file_uri(path)));
+        }
+        push_line(&mut content, "TRANSP:OPAQUE");
+        push_line(&mut content, "END:VEVENT");
+    }
+    push_line(&mut content, "END:VCALENDAR");
+    content
+}
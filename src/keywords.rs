@@ -0,0 +1,255 @@
+//! Keyword packs used to infer what kind of work a prompt or task describes.
+//! Each pack covers one language; the pack for the language a text is
+//! written in is tried first, then English, since technical terms are often
+//! left in English.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Directory inside the diary directory that extra packs are loaded from.
+pub const PACK_DIR: &str = "keyword-packs";
+
+const DEFAULT_LANGUAGE: &str = "en";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeywordPack {
+    /// Language code, e.g. "en", "es", "de", "ja"
+    pub language: String,
+    /// Common words that identify text as written in this language
+    #[serde(default)]
+    pub markers: Vec<String>,
+    /// Tried in order; the first pattern with a matching keyword wins
+    #[serde(default)]
+    pub patterns: Vec<KeywordPattern>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeywordPattern {
+    pub keywords: Vec<String>,
+    pub category: String,
+    /// Default accomplishment description when the text gives no better one
+    pub description: String,
+}
+
+// Categories and descriptions stay in English in every pack so reports
+// group work the same way whatever language it was requested in.
+// (keywords, category, default description)
+const ENGLISH: &[(&str, &str, &str)] = &[
+    // Code Development
+    ("write|create|implement|add|build|develop|code|program", "Code Development", "Implemented new functionality"),
+    ("fix|debug|resolve|solve|repair|correct", "Code Development", "Fixed code issues"),
+    ("refactor|optimize|improve|enhance|update", "Code Development", "Improved code quality"),
+    ("test|unit test|integration test", "Code Development", "Added tests"),
+
+    // Documentation
+    ("document|write docs|readme|comment|explain", "Documentation", "Created documentation"),
+
+    // Analysis & Research
+    ("analyze|investigate|research|study|examine|explore|understand", "Analysis", "Analyzed codebase"),
+    ("find|search|look for|locate", "Code Search", "Searched for information"),
+    ("review|check|verify|validate", "Code Review", "Reviewed code"),
+
+    // Configuration & Setup
+    ("configure|setup|install|deploy|initialize", "System Operations", "Configured system"),
+    ("migrate|upgrade|update dependencies", "System Operations", "Updated dependencies"),
+
+    // Database Operations
+    ("database|sql|query|schema|migration", "Database Operations", "Worked with database"),
+
+    // UI/UX Work
+    ("ui|user interface|frontend|styling|css|design", "Frontend Development", "Worked on user interface"),
+    ("component|react|angular|vue", "Frontend Development", "Developed UI components"),
+
+    // Planning & Organization
+    ("plan|organize|structure|architect|design", "Planning", "Planned project structure"),
+    ("todo|task|milestone|goal", "Project Management", "Managed tasks"),
+];
+
+const SPANISH: &[(&str, &str, &str)] = &[
+    ("corregir|arreglar|depurar|solucionar|resolver|reparar|error|fallo", "Code Development", "Fixed code issues"),
+    ("refactorizar|optimizar|mejorar|actualizar|limpiar", "Code Development", "Improved code quality"),
+    ("prueba|pruebas|testear", "Code Development", "Added tests"),
+    ("implementar|crear|añadir|agregar|escribir|desarrollar|construir|programar", "Code Development", "Implemented new functionality"),
+    ("documentar|documentación|explicar|comentar|léeme", "Documentation", "Created documentation"),
+    ("analizar|investigar|estudiar|examinar|explorar|entender", "Analysis", "Analyzed codebase"),
+    ("buscar|encontrar|localizar", "Code Search", "Searched for information"),
+    ("revisar|verificar|validar|comprobar", "Code Review", "Reviewed code"),
+    ("configurar|instalar|desplegar|inicializar", "System Operations", "Configured system"),
+    ("migrar|dependencias", "System Operations", "Updated dependencies"),
+    ("base de datos|consulta|esquema|migración", "Database Operations", "Worked with database"),
+    ("interfaz|estilos|diseño|componente", "Frontend Development", "Worked on user interface"),
+    ("planificar|planear|organizar|estructurar|arquitectura", "Planning", "Planned project structure"),
+    ("tarea|hito|objetivo", "Project Management", "Managed tasks"),
+];
+
+const GERMAN: &[(&str, &str, &str)] = &[
+    ("beheben|reparieren|korrigieren|debuggen|lösen|fehler|bug", "Code Development", "Fixed code issues"),
+    ("refaktorieren|optimieren|verbessern|aktualisieren|aufräumen", "Code Development", "Improved code quality"),
+    ("testen|tests", "Code Development", "Added tests"),
+    ("implementieren|erstellen|hinzufügen|schreiben|entwickeln|bauen|programmieren", "Code Development", "Implemented new functionality"),
+    ("dokumentieren|dokumentation|erklären|kommentieren", "Documentation", "Created documentation"),
+    ("analysieren|untersuchen|erforschen|verstehen", "Analysis", "Analyzed codebase"),
+    ("suchen|finden", "Code Search", "Searched for information"),
+    ("überprüfen|prüfen|validieren|kontrollieren", "Code Review", "Reviewed code"),
+    ("konfigurieren|einrichten|installieren|deployen|initialisieren", "System Operations", "Configured system"),
+    ("migrieren|abhängigkeiten", "System Operations", "Updated dependencies"),
+    ("datenbank|abfrage|schema|migration", "Database Operations", "Worked with database"),
+    ("oberfläche|styling|gestaltung|komponente", "Frontend Development", "Worked on user interface"),
+    ("planen|organisieren|strukturieren|architektur", "Planning", "Planned project structure"),
+    ("aufgabe|meilenstein|ziel", "Project Management", "Managed tasks"),
+];
+
+const JAPANESE: &[(&str, &str, &str)] = &[
+    ("修正|直して|直す|バグ|デバッグ|解決|エラー", "Code Development", "Fixed code issues"),
+    ("リファクタ|最適化|改善|更新|整理", "Code Development", "Improved code quality"),
+    ("テスト", "Code Development", "Added tests"),
+    ("実装|作成|追加|作って|書いて|開発|構築", "Code Development", "Implemented new functionality"),
+    ("ドキュメント|説明|コメント|文書", "Documentation", "Created documentation"),
+    ("分析|調査|調べ|理解|確かめ", "Analysis", "Analyzed codebase"),
+    ("検索|探して|探す|見つけ", "Code Search", "Searched for information"),
+    ("レビュー|確認|検証", "Code Review", "Reviewed code"),
+    ("設定|インストール|デプロイ|初期化|セットアップ", "System Operations", "Configured system"),
+    ("移行|アップグレード|依存関係", "System Operations", "Updated dependencies"),
+    ("データベース|クエリ|スキーマ|マイグレーション", "Database Operations", "Worked with database"),
+    ("画面|ユーザーインターフェース|フロントエンド|スタイル|デザイン|コンポーネント", "Frontend Development", "Worked on user interface"),
+    ("計画|設計|構成|アーキテクチャ", "Planning", "Planned project structure"),
+    ("タスク|マイルストーン|目標", "Project Management", "Managed tasks"),
+];
+
+fn pack(language: &str, markers: &[&str], patterns: &[(&str, &str, &str)]) -> KeywordPack {
+    KeywordPack {
+        language: language.to_string(),
+        markers: markers.iter().map(|m| m.to_string()).collect(),
+        patterns: patterns
+            .iter()
+            .map(|(keywords, category, description)| KeywordPattern {
+                keywords: keywords.split('|').map(|k| k.to_string()).collect(),
+                category: category.to_string(),
+                description: description.to_string(),
+            })
+            .collect(),
+    }
+}
+
+/// The packs that ship with the hook: English, Spanish, German and Japanese.
+pub fn builtin() -> Vec<KeywordPack> {
+    vec![
+        pack(
+            "en",
+            &["the", "and", "to", "of", "is", "it", "this", "that", "with", "please", "can", "you", "in", "for"],
+            ENGLISH,
+        ),
+        pack(
+            "es",
+            &["el", "la", "los", "las", "de", "que", "y", "en", "por", "para", "una", "con", "del", "es", "no", "por favor"],
+            SPANISH,
+        ),
+        pack(
+            "de",
+            &["der", "die", "das", "und", "ist", "nicht", "mit", "ich", "ein", "eine", "zu", "den", "für", "bitte", "im"],
+            GERMAN,
+        ),
+        pack(
+            "ja",
+            &["は", "が", "を", "に", "の", "で", "て", "です", "ます", "ください", "して"],
+            JAPANESE,
+        ),
+    ]
+}
+
+/// The built-in packs plus any `*.json` packs in `<diary_dir>/keyword-packs`.
+/// A loaded pack for a language that already has one is tried before it.
+pub fn load(diary_dir: &Path) -> Result<Vec<KeywordPack>> {
+    let mut packs = builtin();
+    let dir = diary_dir.join(PACK_DIR);
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(packs);
+    };
+
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read keyword pack: {:?}", path))?;
+        let loaded: KeywordPack = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid keyword pack: {:?}", path))?;
+
+        match packs.iter_mut().find(|p| p.language == loaded.language) {
+            Some(existing) => {
+                existing.markers.extend(loaded.markers);
+                let mut patterns = loaded.patterns;
+                patterns.append(&mut existing.patterns);
+                existing.patterns = patterns;
+            }
+            None => packs.push(loaded),
+        }
+    }
+    Ok(packs)
+}
+
+/// Whether a marker is a word to match whole, or a fragment (such as a
+/// Japanese particle) to count wherever it appears.
+fn is_word(marker: &str) -> bool {
+    marker.chars().all(|c| !c.is_alphabetic() || (c as u32) < 0x2E80)
+}
+
+/// Guess the language of a text from the markers of each pack, falling back
+/// to English when nothing stands out.
+pub fn detect_language<'a>(text: &str, packs: &'a [KeywordPack]) -> &'a str {
+    let lower = text.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    let mut best: Option<(&str, usize)> = None;
+    for pack in packs {
+        let hits: usize = pack
+            .markers
+            .iter()
+            .map(|marker| {
+                if marker.contains(' ') {
+                    lower.matches(marker.as_str()).count()
+                } else if is_word(marker) {
+                    words.iter().filter(|w| *w == marker).count()
+                } else {
+                    lower.matches(marker.as_str()).count()
+                }
+            })
+            .sum();
+        if hits > 0 && best.is_none_or(|(_, most)| hits > most) {
+            best = Some((&pack.language, hits));
+        }
+    }
+
+    best.map(|(language, _)| language)
+        .or_else(|| packs.iter().find(|p| p.language == DEFAULT_LANGUAGE).map(|p| p.language.as_str()))
+        .unwrap_or(DEFAULT_LANGUAGE)
+}
+
+/// Find the category and default description for a piece of work, trying
+/// the pack for the text's language before the English one.
+pub fn classify<'a>(text: &str, packs: &'a [KeywordPack]) -> Option<(&'a str, &'a str)> {
+    let language = detect_language(text, packs);
+    let text_lower = text.to_lowercase();
+
+    let ordered = packs
+        .iter()
+        .filter(|p| p.language == language)
+        .chain(packs.iter().filter(|p| p.language != language && p.language == DEFAULT_LANGUAGE));
+
+    for pack in ordered {
+        let found = pack.patterns.iter().find(|pattern| {
+            pattern.keywords.iter().any(|keyword| text_lower.contains(&keyword.to_lowercase()))
+        });
+        if let Some(pattern) = found {
+            return Some((&pattern.category, &pattern.description));
+        }
+    }
+    None
+}
@@ -1,11 +1,55 @@
-use anyhow::{Context, Result};
-use chrono::{DateTime, Local};
-use clap::Parser;
-use rusqlite::{params, Connection, Row};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, NaiveDate};
+use clap::{Parser, Subcommand};
+use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::{self, BufRead};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+mod activity;
+mod churn;
+mod config;
+mod dates;
+mod file_access;
+mod file_refs;
+mod git;
+mod html;
+mod ics;
+mod keywords;
+mod narrative;
+mod output;
+mod period;
+mod prompt_clean;
+mod references;
+mod standup;
+mod stats;
+mod status;
+mod summarize;
+mod tech_stack;
+mod test_runs;
+mod text;
+mod timesheet;
+mod todos;
+mod tool_failures;
+mod tools;
+mod vault;
+
+use activity::ActivityEvent;
+use churn::LineChurn;
+use config::DiaryConfig;
+use dates::DateRange;
+use file_access::FileAccess;
+use git::{GitCommit, GitSnapshot};
+use narrative::SessionFacts;
+use output::{OutputFormat, Report};
+use prompt_clean::SlashCommand;
+use references::Reference;
+use status::Evidence;
+use tech_stack::Technology;
+use test_runs::TestRun;
+use todos::{TodoItem, TodoTransition};
+use tool_failures::{ToolFailure, ToolStats};
 
 #[derive(Parser, Debug)]
 #[command(name = "claude-diary-hook")]
@@ -25,10 +69,143 @@ struct Args {
     
     #[arg(long, help = "Number of recent sessions to show", default_value = "5")]
     limit: usize,
+    
+    #[arg(long, global = true, help = "Also show accomplishments that are requested, in progress or abandoned")]
+    include_unconfirmed: bool,
+    
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Markdown, help = "Output format for reports")]
+    format: OutputFormat,
+    
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List every session and accomplishment tied to a ticket or issue
+    Ticket {
+        /// Ticket key, issue number or URL, e.g. PROJ-1234 or #512
+        key: String,
+    },
+    /// Show which tools fail most often and usage per MCP server
+    Tools,
+    /// Show time spent per language, framework and tool
+    Tech {
+        /// First day to include, e.g. 2026-10-01 or this-month
+        #[arg(long, value_parser = dates::parse_day_arg)]
+        since: Option<NaiveDate>,
+        /// Last day to include
+        #[arg(long, value_parser = dates::parse_day_arg)]
+        until: Option<NaiveDate>,
+    },
+    /// Yesterday / today / blockers from the previous working day, ready to paste
+    Standup {
+        /// The day of the standup (defaults to today); weekends are skipped when looking back
+        #[arg(long, value_parser = dates::parse_day_arg)]
+        date: Option<NaiveDate>,
+        /// Plain text instead of Markdown
+        #[arg(long)]
+        plain: bool,
+    },
+    /// Weekly or monthly summary compared with the period before (weekly unless --month is given)
+    Report {
+        /// Report on a Monday-to-Sunday week (the default)
+        #[arg(long, conflicts_with = "month")]
+        week: bool,
+        /// Report on a calendar month
+        #[arg(long)]
+        month: bool,
+        /// A day in the period to report on (defaults to today)
+        #[arg(long, value_parser = dates::parse_day_arg)]
+        date: Option<NaiveDate>,
+        /// Write a self-contained HTML report with charts to this file
+        #[arg(long, value_name = "PATH")]
+        html: Option<PathBuf>,
+    },
+    /// Hours per day and project as CSV, for billing (defaults to this week)
+    Timesheet {
+        /// today, yesterday, this-week, last-week, this-month, last-month, 7d or a date
+        #[arg(conflicts_with_all = ["since", "until"])]
+        period: Option<String>,
+        /// First day to include
+        #[arg(long, value_parser = dates::parse_day_arg)]
+        since: Option<NaiveDate>,
+        /// Last day to include
+        #[arg(long, value_parser = dates::parse_day_arg)]
+        until: Option<NaiveDate>,
+    },
+    /// Export the diary to other tools: `--format ics` for calendar events, or a target below
+    #[command(args_conflicts_with_subcommands = true)]
+    Export {
+        #[command(subcommand)]
+        target: Option<ExportTarget>,
+        /// With --format ics: today, yesterday, this-week, last-week, this-month, last-month, 7d or a date (defaults to all time)
+        #[arg(conflicts_with_all = ["since", "until"])]
+        period: Option<String>,
+        /// First day to include
+        #[arg(long, value_parser = dates::parse_day_arg)]
+        since: Option<NaiveDate>,
+        /// Last day to include
+        #[arg(long, value_parser = dates::parse_day_arg)]
+        until: Option<NaiveDate>,
+    },
+    /// Usage analytics: tool trends, busiest files, categories, errors and streaks (defaults to all time)
+    Stats {
+        /// today, yesterday, this-week, last-week, this-month, last-month, 7d or a date
+        #[arg(conflicts_with_all = ["since", "until"])]
+        period: Option<String>,
+        /// First day to include
+        #[arg(long, value_parser = dates::parse_day_arg)]
+        since: Option<NaiveDate>,
+        /// Last day to include
+        #[arg(long, value_parser = dates::parse_day_arg)]
+        until: Option<NaiveDate>,
+        /// Only sessions in this project (the working directory's name)
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Show diary entries for a day or a range of days (defaults to today)
+    Show {
+        /// today, yesterday, this-week, last-week, this-month, "last monday", 7d or a date
+        #[arg(conflicts_with_all = ["date", "since", "until"])]
+        period: Option<String>,
+        /// A single day, e.g. 2026-10-15
+        #[arg(long, value_parser = dates::parse_day_arg, conflicts_with_all = ["since", "until"])]
+        date: Option<NaiveDate>,
+        /// First day to include
+        #[arg(long, value_parser = dates::parse_day_arg)]
+        since: Option<NaiveDate>,
+        /// Last day to include
+        #[arg(long, value_parser = dates::parse_day_arg)]
+        until: Option<NaiveDate>,
+        /// Show at most this many sessions
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ExportTarget {
+    /// Write each day's diary into a daily note in a Markdown vault (defaults to today)
+    Vault {
+        /// today, yesterday, this-week, last-week, this-month, last-month, 7d or a date
+        #[arg(conflicts_with_all = ["since", "until"])]
+        period: Option<String>,
+        /// First day to include
+        #[arg(long, value_parser = dates::parse_day_arg)]
+        since: Option<NaiveDate>,
+        /// Last day to include
+        #[arg(long, value_parser = dates::parse_day_arg)]
+        until: Option<NaiveDate>,
+        /// Vault directory, instead of vault.path from the config
+        #[arg(long)]
+        vault: Option<String>,
+    },
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default)]
 struct ClaudeEvent {
+    #[serde(alias = "hook_event_name")]
     event_type: String,
     timestamp: Option<String>,
     context: Option<serde_json::Value>,
@@ -38,27 +215,132 @@ struct ClaudeEvent {
     tool_calls: Option<Vec<ToolCall>>,
     duration_ms: Option<u64>,
     error: Option<String>,
+    // Fields sent by Claude Code hooks, folded into the ones above by `normalize`
+    cwd: Option<String>,
+    prompt: Option<String>,
+    tool_name: Option<String>,
+    tool_input: Option<serde_json::Value>,
+    tool_response: Option<serde_json::Value>,
+    transcript_path: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default)]
 struct ToolCall {
     tool_name: String,
     parameters: Option<serde_json::Value>,
     result: Option<String>,
     duration_ms: Option<u64>,
     success: Option<bool>,
+    /// The raw hook `tool_response`, kept for details `result` flattens away
+    #[serde(skip)]
+    response: Option<serde_json::Value>,
+}
+
+impl ClaudeEvent {
+    /// Map Claude Code hook payloads (`hook_event_name`, `prompt`, `tool_input`,
+    /// `tool_response`) onto the event shape the rest of the hook understands.
+    fn normalize(mut self) -> Self {
+        self.event_type = match self.event_type.as_str() {
+            "UserPromptSubmit" => "user_prompt".to_string(),
+            "PostToolUse" => "tool_result".to_string(),
+            "SessionStart" => "session_start".to_string(),
+            "SessionEnd" => "session_end".to_string(),
+            "Stop" => "stop".to_string(),
+            other => other.to_string(),
+        };
+
+        if self.user_prompt.is_none() {
+            self.user_prompt = self.prompt.take();
+        }
+        
+        // Slash commands are typed like prompts but are their own kind of event
+        if self.event_type == "user_prompt"
+            && self.user_prompt.as_deref().and_then(prompt_clean::slash_command).is_some()
+        {
+            self.event_type = "slash_command".to_string();
+        }
+
+        if self.cwd.is_none() {
+            self.cwd = self
+                .context
+                .as_ref()
+                .and_then(|c| c.get("cwd"))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+        }
+
+        if self.tool_calls.is_none() {
+            if let Some(tool_name) = self.tool_name.take() {
+                let response = self.tool_response.take();
+                let success = response.as_ref().map(|r| !tool_failures::response_failed(r));
+                self.tool_calls = Some(vec![ToolCall {
+                    tool_name,
+                    parameters: self.tool_input.take(),
+                    result: response.as_ref().map(tool_response_text),
+                    duration_ms: None,
+                    success,
+                    response,
+                }]);
+            }
+        }
+
+        self
+    }
+
+    /// When the event happened, falling back to now if it carries no timestamp.
+    fn occurred_at(&self) -> DateTime<Local> {
+        self.timestamp
+            .as_deref()
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+            .map(|ts| ts.with_timezone(&Local))
+            .unwrap_or_else(Local::now)
+    }
+}
+
+/// Flatten a hook `tool_response` into the text a user would have seen.
+fn tool_response_text(response: &serde_json::Value) -> String {
+    match response {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Object(obj) => {
+            let streams: Vec<&str> = ["stdout", "stderr", "output", "content"]
+                .iter()
+                .filter_map(|key| obj.get(*key).and_then(|v| v.as_str()))
+                .filter(|s| !s.is_empty())
+                .collect();
+            if streams.is_empty() {
+                response.to_string()
+            } else {
+                streams.join("\n")
+            }
+        }
+        other => other.to_string(),
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 struct DiarySession {
     start_time: DateTime<Local>,
     end_time: Option<DateTime<Local>>,
-    objectives: Vec<String>,
+    objectives: Vec<(String, DateTime<Local>)>,
     accomplishments: Vec<Accomplishment>,
-    issues: Vec<String>,
+    issues: Vec<(String, DateTime<Local>)>,
     files_modified: Vec<String>,
+    file_churn: Vec<(String, LineChurn)>,
+    file_accesses: Vec<FileAccess>,
+    technologies: Vec<Technology>,
     tool_usage: HashMap<String, u32>,
+    tool_failures: Vec<ToolFailure>,
+    test_runs: Vec<TestRun>,
+    git_snapshots: Vec<GitSnapshot>,
+    commits: Vec<GitCommit>,
+    references: Vec<Reference>,
+    slash_commands: Vec<(SlashCommand, DateTime<Local>)>,
+    todos: Vec<TodoItem>,
+    todo_transitions: Vec<TodoTransition>,
+    events: Vec<ActivityEvent>,
+    /// Active time, from the event timestamps split at idle gaps
     total_duration_ms: u64,
+    active_segments: usize,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -67,6 +349,16 @@ struct Accomplishment {
     description: String,
     duration_ms: Option<u64>,
     files_affected: Vec<String>,
+    #[serde(default)]
+    references: Vec<Reference>,
+    /// Where the accomplishment came from: "prompt", "tool", "response" or "todo"
+    #[serde(default)]
+    source: String,
+    /// How far later evidence has confirmed it, see `status`
+    #[serde(default)]
+    status: String,
+    /// When the event it came from happened
+    recorded_at: DateTime<Local>,
 }
 
 impl DiarySession {
@@ -78,16 +370,53 @@ impl DiarySession {
             accomplishments: Vec::new(),
             issues: Vec::new(),
             files_modified: Vec::new(),
+            file_churn: Vec::new(),
+            file_accesses: Vec::new(),
+            technologies: Vec::new(),
             tool_usage: HashMap::new(),
+            tool_failures: Vec::new(),
+            test_runs: Vec::new(),
+            git_snapshots: Vec::new(),
+            commits: Vec::new(),
+            references: Vec::new(),
+            slash_commands: Vec::new(),
+            todos: Vec::new(),
+            todo_transitions: Vec::new(),
+            events: Vec::new(),
             total_duration_ms: 0,
+            active_segments: 0,
         }
     }
 }
 
+/// A stored session, as the commands that work through a date range see it.
+struct StoredSession {
+    id: i64,
+    /// The local day it started on
+    day: NaiveDate,
+    active_ms: i64,
+    cwd: Option<String>,
+}
+
 struct DiaryManager {
     db_path: PathBuf,
     current_session_id: Option<i64>,
     current_session: DiarySession,
+    config: DiaryConfig,
+    keyword_packs: Vec<keywords::KeywordPack>,
+    claude_session_id: Option<String>,
+    cwd: Option<String>,
+    saved_tool_usage: HashMap<String, u32>,
+    saved_tool_failures: usize,
+    saved_test_runs: usize,
+    saved_git_snapshots: usize,
+    saved_file_churn: usize,
+    saved_file_accesses: usize,
+    saved_todo_transitions: usize,
+    saved_slash_commands: usize,
+    saved_events: usize,
+    include_unconfirmed: bool,
+    format: OutputFormat,
     verbose: bool,
     test_mode: bool,
 }
@@ -106,6 +435,8 @@ impl DiaryManager {
         }
 
         let db_path = diary_dir.join("diary.db");
+        let config = DiaryConfig::load(&diary_dir)?;
+        let keyword_packs = keywords::load(&diary_dir)?;
         
         // Handle migration from old directory structure
         if !test_mode {
@@ -126,10 +457,25 @@ impl DiaryManager {
             }
         }
         
-        let mut manager = Self {
+        let manager = Self {
             db_path,
             current_session_id: None,
             current_session: DiarySession::new(),
+            config,
+            keyword_packs,
+            claude_session_id: None,
+            cwd: None,
+            saved_tool_usage: HashMap::new(),
+            saved_tool_failures: 0,
+            saved_test_runs: 0,
+            saved_git_snapshots: 0,
+            saved_file_churn: 0,
+            saved_file_accesses: 0,
+            saved_todo_transitions: 0,
+            saved_slash_commands: 0,
+            saved_events: 0,
+            include_unconfirmed: false,
+            format: OutputFormat::Markdown,
             verbose,
             test_mode,
         };
@@ -223,6 +569,172 @@ impl DiaryManager {
             [],
         )?;
         
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS file_access (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                file_path TEXT NOT NULL,
+                access_type TEXT NOT NULL,
+                tool_name TEXT NOT NULL,
+                accessed_at TEXT NOT NULL,
+                FOREIGN KEY (session_id) REFERENCES sessions (id)
+            )",
+            [],
+        )?;
+        
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS session_technologies (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                evidence TEXT NOT NULL,
+                UNIQUE (session_id, name),
+                FOREIGN KEY (session_id) REFERENCES sessions (id)
+            )",
+            [],
+        )?;
+        
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS slash_commands (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                args TEXT,
+                invoked_at TEXT NOT NULL,
+                FOREIGN KEY (session_id) REFERENCES sessions (id)
+            )",
+            [],
+        )?;
+        
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS session_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                event_type TEXT NOT NULL,
+                occurred_at TEXT NOT NULL,
+                duration_ms INTEGER,
+                FOREIGN KEY (session_id) REFERENCES sessions (id)
+            )",
+            [],
+        )?;
+        
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS daily_summaries (
+                day TEXT PRIMARY KEY,
+                narrative TEXT NOT NULL,
+                session_count INTEGER NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS test_runs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                runner TEXT NOT NULL,
+                passed INTEGER NOT NULL DEFAULT 0,
+                failed INTEGER NOT NULL DEFAULT 0,
+                skipped INTEGER NOT NULL DEFAULT 0,
+                failing_tests TEXT,
+                duration_ms INTEGER,
+                run_at TEXT NOT NULL,
+                FOREIGN KEY (session_id) REFERENCES sessions (id)
+            )",
+            [],
+        )?;
+        
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS git_snapshots (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                head TEXT,
+                branch TEXT,
+                dirty INTEGER NOT NULL DEFAULT 0,
+                taken_at TEXT NOT NULL,
+                FOREIGN KEY (session_id) REFERENCES sessions (id)
+            )",
+            [],
+        )?;
+        
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS git_commits (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                hash TEXT NOT NULL,
+                subject TEXT NOT NULL,
+                files_changed INTEGER NOT NULL DEFAULT 0,
+                insertions INTEGER NOT NULL DEFAULT 0,
+                deletions INTEGER NOT NULL DEFAULT 0,
+                committed_at TEXT NOT NULL,
+                UNIQUE (session_id, hash),
+                FOREIGN KEY (session_id) REFERENCES sessions (id)
+            )",
+            [],
+        )?;
+        
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS `references` (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                accomplishment_id INTEGER,
+                kind TEXT NOT NULL,
+                ref_key TEXT NOT NULL,
+                source TEXT NOT NULL,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (session_id) REFERENCES sessions (id),
+                FOREIGN KEY (accomplishment_id) REFERENCES accomplishments (id)
+            )",
+            [],
+        )?;
+        
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS todos (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                content TEXT NOT NULL,
+                status TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                completed_at TEXT,
+                UNIQUE (session_id, content),
+                FOREIGN KEY (session_id) REFERENCES sessions (id)
+            )",
+            [],
+        )?;
+        
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS todo_transitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                todo_id INTEGER NOT NULL,
+                from_status TEXT,
+                to_status TEXT NOT NULL,
+                changed_at TEXT NOT NULL,
+                FOREIGN KEY (todo_id) REFERENCES todos (id)
+            )",
+            [],
+        )?;
+        
+        // Columns added after the initial release
+        add_column_if_missing(&conn, "sessions", "claude_session_id", "TEXT")?;
+        add_column_if_missing(&conn, "sessions", "cwd", "TEXT")?;
+        add_column_if_missing(&conn, "sessions", "narrative", "TEXT")?;
+        add_column_if_missing(&conn, "sessions", "active_segments", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column_if_missing(&conn, "files_modified", "lines_added", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column_if_missing(&conn, "files_modified", "lines_removed", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column_if_missing(&conn, "files_modified", "edit_count", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column_if_missing(&conn, "accomplishments", "source", "TEXT")?;
+        add_column_if_missing(&conn, "accomplishment_files", "resolved_path", "TEXT")?;
+        add_column_if_missing(&conn, "accomplishment_files", "file_exists", "INTEGER")?;
+        add_column_if_missing(&conn, "accomplishments", "status", "TEXT")?;
+        add_column_if_missing(&conn, "tool_usage", "success_count", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column_if_missing(&conn, "tool_usage", "failure_count", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column_if_missing(&conn, "tool_usage", "mcp_server", "TEXT")?;
+        add_column_if_missing(&conn, "tool_usage", "mcp_tool", "TEXT")?;
+        add_column_if_missing(&conn, "issues", "tool_name", "TEXT")?;
+        add_column_if_missing(&conn, "issues", "file_path", "TEXT")?;
+        
         if self.verbose {
             eprintln!("Database initialized: {:?}", self.db_path);
         }
@@ -241,9 +753,35 @@ impl DiaryManager {
         }
         
         let conn = Connection::open(&self.db_path)?;
+        
+        // Each hook invocation is a separate process, so pick up the session
+        // an earlier event from the same Claude Code session already created
+        if let Some(claude_session_id) = &self.claude_session_id {
+            let existing: Option<i64> = conn.query_row(
+                "SELECT id FROM sessions WHERE claude_session_id = ?1 ORDER BY id DESC LIMIT 1",
+                params![claude_session_id],
+                |row| row.get(0),
+            ).ok();
+            
+            if let Some(session_id) = existing {
+                if self.cwd.is_some() {
+                    conn.execute(
+                        "UPDATE sessions SET cwd = COALESCE(cwd, ?1) WHERE id = ?2",
+                        params![self.cwd, session_id],
+                    )?;
+                }
+                self.current_session_id = Some(session_id);
+                return Ok(session_id);
+            }
+        }
+        
         let session_id = conn.query_row(
-            "INSERT INTO sessions (start_time) VALUES (?1) RETURNING id",
-            params![self.current_session.start_time.to_rfc3339()],
+            "INSERT INTO sessions (start_time, claude_session_id, cwd) VALUES (?1, ?2, ?3) RETURNING id",
+            params![
+                self.current_session.start_time.to_rfc3339(),
+                self.claude_session_id,
+                self.cwd
+            ],
             |row| row.get(0),
         )?;
         
@@ -256,6 +794,31 @@ impl DiaryManager {
             eprintln!("Processing event: {:?}", event.event_type);
         }
 
+        if self.claude_session_id.is_none() {
+            self.claude_session_id = event.session_id.clone();
+        }
+        if self.cwd.is_none() {
+            self.cwd = event.cwd.clone();
+        }
+
+        // Active time is worked out from when events arrive
+        let occurred_at = event.occurred_at();
+        if self.current_session.events.is_empty() {
+            self.current_session.start_time = occurred_at;
+        }
+        self.current_session.events.push(ActivityEvent {
+            event_type: event.event_type.clone(),
+            occurred_at,
+            duration_ms: event.duration_ms,
+        });
+
+        // Snapshot the repository at the session boundaries and whenever
+        // Claude finishes responding; commits are diffed against the first one
+        match event.event_type.as_str() {
+            "session_start" | "session_end" | "stop" => self.capture_git_activity()?,
+            _ => self.ensure_git_baseline()?,
+        }
+
         // Update tool usage statistics
         if let Some(tool_calls) = &event.tool_calls {
             for tool_call in tool_calls {
@@ -265,11 +828,6 @@ impl DiaryManager {
             }
         }
 
-        // Add duration
-        if let Some(duration) = event.duration_ms {
-            self.current_session.total_duration_ms += duration;
-        }
-
         // Analyze event for accomplishments and issues
         match event.event_type.as_str() {
             "session_start" | "user_prompt" | "message" => {
@@ -277,6 +835,11 @@ impl DiaryManager {
                 // Save immediately for concurrent access
                 self.save_current_data()?;
             }
+            "slash_command" => {
+                self.process_slash_command(&event);
+                // Save immediately for concurrent access
+                self.save_current_data()?;
+            }
             "tool_call" | "tool_result" => {
                 self.process_tool_activity(&event)?;
                 // Save immediately for concurrent access
@@ -287,9 +850,25 @@ impl DiaryManager {
                 // Save immediately for concurrent access
                 self.save_current_data()?;
             }
+            "stop" => {
+                self.process_generic_activity(&event);
+                self.apply_evidence(Evidence::Stop)?;
+                // Save immediately for concurrent access
+                self.save_current_data()?;
+            }
             "session_end" => {
-                self.current_session.end_time = Some(Local::now());
+                self.apply_evidence(Evidence::SessionEnd)?;
+                self.current_session.end_time = Some(occurred_at);
                 self.save_session_to_db()?;
+                if self.config.vault.on_session_end && !self.test_mode {
+                    let conn = Connection::open(&self.db_path)?;
+                    // A vault that can't be written shouldn't fail the hook
+                    match self.write_vault_note(&conn, occurred_at.date_naive(), None) {
+                        Ok(Some(path)) if self.verbose => eprintln!("Updated daily note {}", path.display()),
+                        Err(e) => eprintln!("Failed to update daily note: {}", e),
+                        _ => {}
+                    }
+                }
             }
             _ => {
                 // Generic processing for other event types
@@ -310,10 +889,30 @@ impl DiaryManager {
         let session_id = self.get_or_create_session()?;
         let conn = Connection::open(&self.db_path)?;
         
-        // Update session duration
+        // Save event timings since the last save, then recompute active time
+        // over every event in the session, including earlier hook processes
+        for event in &self.current_session.events[self.saved_events..] {
+            conn.execute(
+                "INSERT INTO session_events (session_id, event_type, occurred_at, duration_ms) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    session_id,
+                    &event.event_type,
+                    event.occurred_at.to_rfc3339(),
+                    event.duration_ms.map(|d| d as i64)
+                ],
+            )?;
+        }
+        self.saved_events = self.current_session.events.len();
+        
+        let events = self.load_session_events(&conn, session_id)?;
+        (self.current_session.total_duration_ms, self.current_session.active_segments) = self.active_time(&events);
         conn.execute(
-            "UPDATE sessions SET total_duration_ms = ?1 WHERE id = ?2",
-            params![self.current_session.total_duration_ms as i64, session_id],
+            "UPDATE sessions SET total_duration_ms = ?1, active_segments = ?2 WHERE id = ?3",
+            params![
+                self.current_session.total_duration_ms as i64,
+                self.current_session.active_segments as i64,
+                session_id
+            ],
         )?;
         
         // Save new accomplishments (check if already saved)
@@ -326,29 +925,60 @@ impl DiaryManager {
             
             if !exists {
                 let acc_id: i64 = conn.query_row(
-                    "INSERT INTO accomplishments (session_id, category, description, duration_ms) 
-                     VALUES (?1, ?2, ?3, ?4) RETURNING id",
+                    "INSERT INTO accomplishments (session_id, category, description, duration_ms, source, status, created_at) 
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7) RETURNING id",
                     params![
                         session_id,
                         &accomplishment.category,
                         &accomplishment.description,
-                        accomplishment.duration_ms.map(|d| d as i64)
+                        accomplishment.duration_ms.map(|d| d as i64),
+                        &accomplishment.source,
+                        &accomplishment.status,
+                        accomplishment.recorded_at.to_rfc3339()
                     ],
                     |row| row.get(0),
                 )?;
                 
-                // Save files affected by this accomplishment
+                // Save files affected by this accomplishment, resolved against the session directory
                 for file_path in &accomplishment.files_affected {
+                    let (resolved, exists) = file_refs::resolve(file_path, self.cwd.as_deref().map(Path::new));
+                    conn.execute(
+                        "INSERT INTO accomplishment_files (accomplishment_id, file_path, resolved_path, file_exists) 
+                         VALUES (?1, ?2, ?3, ?4)",
+                        params![acc_id, file_path, resolved, exists],
+                    )?;
+                }
+                
+                // Save ticket and issue references made alongside it
+                for reference in &accomplishment.references {
                     conn.execute(
-                        "INSERT INTO accomplishment_files (accomplishment_id, file_path) VALUES (?1, ?2)",
-                        params![acc_id, file_path],
+                        "INSERT INTO `references` (session_id, accomplishment_id, kind, ref_key, source) 
+                         VALUES (?1, ?2, ?3, ?4, ?5)",
+                        params![session_id, acc_id, &reference.kind, &reference.key, &reference.source],
                     )?;
                 }
             }
         }
         
+        // Save references tied to the session as a whole
+        for reference in &self.current_session.references {
+            let exists: bool = conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM `references` 
+                 WHERE session_id = ?1 AND ref_key = ?2 AND source = ?3 AND accomplishment_id IS NULL)",
+                params![session_id, &reference.key, &reference.source],
+                |row| row.get(0),
+            ).unwrap_or(false);
+            
+            if !exists {
+                conn.execute(
+                    "INSERT INTO `references` (session_id, kind, ref_key, source) VALUES (?1, ?2, ?3, ?4)",
+                    params![session_id, &reference.kind, &reference.key, &reference.source],
+                )?;
+            }
+        }
+        
         // Save new objectives
-        for objective in &self.current_session.objectives {
+        for (objective, recorded_at) in &self.current_session.objectives {
             let exists: bool = conn.query_row(
                 "SELECT EXISTS(SELECT 1 FROM objectives WHERE session_id = ?1 AND objective = ?2)",
                 params![session_id, objective],
@@ -357,125 +987,377 @@ impl DiaryManager {
             
             if !exists {
                 conn.execute(
-                    "INSERT INTO objectives (session_id, objective) VALUES (?1, ?2)",
-                    params![session_id, objective],
+                    "INSERT INTO objectives (session_id, objective, created_at) VALUES (?1, ?2, ?3)",
+                    params![session_id, objective, recorded_at.to_rfc3339()],
                 )?;
             }
         }
         
-        // Update tool usage (upsert)
-        for (tool_name, count) in &self.current_session.tool_usage {
+        // Save new issues
+        for (issue, recorded_at) in &self.current_session.issues {
+            let exists: bool = conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM issues WHERE session_id = ?1 AND issue = ?2)",
+                params![session_id, issue],
+                |row| row.get(0),
+            ).unwrap_or(false);
+            
+            if !exists {
+                conn.execute(
+                    "INSERT INTO issues (session_id, issue, created_at) VALUES (?1, ?2, ?3)",
+                    params![session_id, issue, recorded_at.to_rfc3339()],
+                )?;
+            }
+        }
+        
+        // Save newly modified files
+        for file_path in &self.current_session.files_modified {
+            let exists: bool = conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM files_modified WHERE session_id = ?1 AND file_path = ?2)",
+                params![session_id, file_path],
+                |row| row.get(0),
+            ).unwrap_or(false);
+            
+            if !exists {
+                conn.execute(
+                    "INSERT INTO files_modified (session_id, file_path) VALUES (?1, ?2)",
+                    params![session_id, file_path],
+                )?;
+            }
+        }
+        
+        // Save file accesses since the last save
+        for access in &self.current_session.file_accesses[self.saved_file_accesses..] {
             conn.execute(
-                "INSERT OR REPLACE INTO tool_usage (session_id, tool_name, usage_count) 
-                 VALUES (?1, ?2, ?3)",
-                params![session_id, tool_name, *count as i64],
+                "INSERT INTO file_access (session_id, file_path, access_type, tool_name, accessed_at) 
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    session_id,
+                    &access.path,
+                    &access.access_type,
+                    &access.tool_name,
+                    access.accessed_at.to_rfc3339()
+                ],
             )?;
         }
+        self.saved_file_accesses = self.current_session.file_accesses.len();
         
-        
-        Ok(())
-    }
-
-    fn infer_objectives_and_accomplishments(&mut self, event: &ClaudeEvent) {
-        if let Some(prompt) = &event.user_prompt {
-            // Try to parse the prompt as JSON first
-            let actual_prompt = if prompt.starts_with('{') && prompt.ends_with('}') {
-                // Attempt to parse as JSON to extract the actual prompt
-                if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(prompt) {
-                    // Try different possible field names for the actual prompt
-                    json_val.get("prompt")
-                        .or_else(|| json_val.get("user_prompt"))
-                        .or_else(|| json_val.get("message"))
-                        .or_else(|| json_val.get("text"))
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string())
-                        .unwrap_or_else(|| prompt.clone())
-                } else {
-                    prompt.clone()
-                }
-            } else {
-                prompt.clone()
-            };
-            
-            // Extract objectives from user prompts
-            let objective = if actual_prompt.len() > 100 {
-                format!("{}", actual_prompt.chars().take(100).collect::<String>())
-            } else {
-                actual_prompt.clone()
-            };
-            
-            self.current_session.objectives.push(objective);
-            
-            // Infer accomplishments from user prompts
-            self.infer_accomplishments_from_prompt(&actual_prompt, event.duration_ms);
+        // Save slash commands since the last save
+        for (command, invoked_at) in &self.current_session.slash_commands[self.saved_slash_commands..] {
+            conn.execute(
+                "INSERT INTO slash_commands (session_id, name, args, invoked_at) VALUES (?1, ?2, ?3, ?4)",
+                params![session_id, &command.name, &command.args, invoked_at.to_rfc3339()],
+            )?;
         }
-    }
-
-    fn infer_accomplishments_from_prompt(&mut self, prompt: &str, duration_ms: Option<u64>) {
-        let prompt_lower = prompt.to_lowercase();
+        self.saved_slash_commands = self.current_session.slash_commands.len();
         
-        // Define patterns for different types of accomplishments
-        let patterns = [
-            // Code Development
-            ("write|create|implement|add|build|develop|code|program", "Code Development", "Implemented new functionality"),
-            ("fix|debug|resolve|solve|repair|correct", "Code Development", "Fixed code issues"),
-            ("refactor|optimize|improve|enhance|update", "Code Development", "Improved code quality"),
-            ("test|unit test|integration test", "Code Development", "Added tests"),
+        // Save todo status changes since the last save
+        for transition in &self.current_session.todo_transitions[self.saved_todo_transitions..] {
+            let changed_at = transition.changed_at.to_rfc3339();
+            let completed_at = (transition.to == todos::COMPLETED).then(|| changed_at.clone());
             
-            // Documentation
-            ("document|write docs|readme|comment|explain", "Documentation", "Created documentation"),
-            
-            // Analysis & Research
-            ("analyze|investigate|research|study|examine|explore|understand", "Analysis", "Analyzed codebase"),
-            ("find|search|look for|locate", "Code Search", "Searched for information"),
-            ("review|check|verify|validate", "Code Review", "Reviewed code"),
-            
-            // Configuration & Setup
-            ("configure|setup|install|deploy|initialize", "System Operations", "Configured system"),
-            ("migrate|upgrade|update dependencies", "System Operations", "Updated dependencies"),
-            
-            // Database Operations
-            ("database|sql|query|schema|migration", "Database Operations", "Worked with database"),
-            
-            // UI/UX Work
-            ("ui|user interface|frontend|styling|css|design", "Frontend Development", "Worked on user interface"),
-            ("component|react|angular|vue", "Frontend Development", "Developed UI components"),
-            
-            // Planning & Organization
-            ("plan|organize|structure|architect|design", "Planning", "Planned project structure"),
-            ("todo|task|milestone|goal", "Project Management", "Managed tasks"),
-        ];
+            conn.execute(
+                "INSERT OR IGNORE INTO todos (session_id, content, status, created_at, updated_at) 
+                 VALUES (?1, ?2, ?3, ?4, ?4)",
+                params![session_id, &transition.content, &transition.to, &changed_at],
+            )?;
+            let todo_id: i64 = conn.query_row(
+                "UPDATE todos SET status = ?1, updated_at = ?2, completed_at = COALESCE(?3, completed_at) 
+                 WHERE session_id = ?4 AND content = ?5 RETURNING id",
+                params![&transition.to, &changed_at, completed_at, session_id, &transition.content],
+                |row| row.get(0),
+            )?;
+            conn.execute(
+                "INSERT INTO todo_transitions (todo_id, from_status, to_status, changed_at) VALUES (?1, ?2, ?3, ?4)",
+                params![todo_id, &transition.from, &transition.to, &changed_at],
+            )?;
+        }
+        self.saved_todo_transitions = self.current_session.todo_transitions.len();
         
-        let mut found_accomplishment = false;
+        // Add line churn from edits made since the last save
+        for (file_path, churn) in &self.current_session.file_churn[self.saved_file_churn..] {
+            conn.execute(
+                "UPDATE files_modified 
+                 SET lines_added = lines_added + ?1, lines_removed = lines_removed + ?2, edit_count = edit_count + 1 
+                 WHERE id = (SELECT MIN(id) FROM files_modified WHERE session_id = ?3 AND file_path = ?4)",
+                params![churn.added, churn.removed, session_id, file_path],
+            )?;
+        }
+        self.saved_file_churn = self.current_session.file_churn.len();
         
-        for (pattern, category, default_description) in patterns.iter() {
-            if pattern.split('|').any(|p| prompt_lower.contains(p)) {
-                let description = self.generate_accomplishment_description(prompt, default_description);
-                
-                let accomplishment = Accomplishment {
-                    category: category.to_string(),
-                    description,
-                    duration_ms,
-                    files_affected: self.extract_files_from_prompt(prompt),
-                };
-                
-                self.current_session.accomplishments.push(accomplishment);
-                found_accomplishment = true;
-                break; // Only create one accomplishment per prompt to avoid duplicates
+        // Update tool usage, adding only the calls seen since the last save
+        let new_failures = &self.current_session.tool_failures[self.saved_tool_failures..];
+        for (tool_name, count) in &self.current_session.tool_usage {
+            let saved = self.saved_tool_usage.get(tool_name).copied().unwrap_or(0);
+            if *count <= saved {
+                continue;
+            }
+            let delta = (*count - saved) as i64;
+            let failed = new_failures.iter().filter(|f| &f.tool_name == tool_name).count() as i64;
+            
+            let updated = conn.execute(
+                "UPDATE tool_usage 
+                 SET usage_count = usage_count + ?1, success_count = success_count + ?2, failure_count = failure_count + ?3 
+                 WHERE id = (SELECT MIN(id) FROM tool_usage WHERE session_id = ?4 AND tool_name = ?5)",
+                params![delta, delta - failed, failed, session_id, tool_name],
+            )?;
+            if updated == 0 {
+                let mcp = tools::parse_mcp(tool_name);
+                conn.execute(
+                    "INSERT INTO tool_usage 
+                     (session_id, tool_name, usage_count, success_count, failure_count, mcp_server, mcp_tool) 
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        session_id,
+                        tool_name,
+                        delta,
+                        delta - failed,
+                        failed,
+                        mcp.as_ref().map(|m| m.server),
+                        mcp.as_ref().map(|m| m.tool)
+                    ],
+                )?;
+            }
+        }
+        self.saved_tool_usage = self.current_session.tool_usage.clone();
+        
+        // Save failed tool calls as issues
+        for failure in new_failures {
+            conn.execute(
+                "INSERT INTO issues (session_id, issue, tool_name, file_path, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    session_id,
+                    failure.issue_text(),
+                    &failure.tool_name,
+                    &failure.file_path,
+                    failure.failed_at.to_rfc3339()
+                ],
+            )?;
+        }
+        self.saved_tool_failures = self.current_session.tool_failures.len();
+        
+        // Save new test runs
+        for run in &self.current_session.test_runs[self.saved_test_runs..] {
+            conn.execute(
+                "INSERT INTO test_runs (session_id, runner, passed, failed, skipped, failing_tests, duration_ms, run_at) 
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    session_id,
+                    &run.runner,
+                    run.passed,
+                    run.failed,
+                    run.skipped,
+                    serde_json::to_string(&run.failing_tests)?,
+                    run.duration_ms.map(|d| d as i64),
+                    run.run_at.to_rfc3339()
+                ],
+            )?;
+        }
+        self.saved_test_runs = self.current_session.test_runs.len();
+        
+        // Save new git snapshots and commits
+        for snapshot in &self.current_session.git_snapshots[self.saved_git_snapshots..] {
+            conn.execute(
+                "INSERT INTO git_snapshots (session_id, head, branch, dirty, taken_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    session_id,
+                    snapshot.head,
+                    snapshot.branch,
+                    snapshot.dirty,
+                    snapshot.taken_at.to_rfc3339()
+                ],
+            )?;
+        }
+        self.saved_git_snapshots = self.current_session.git_snapshots.len();
+        
+        for tech in &self.current_session.technologies {
+            conn.execute(
+                "INSERT OR IGNORE INTO session_technologies (session_id, name, kind, evidence) VALUES (?1, ?2, ?3, ?4)",
+                params![session_id, &tech.name, &tech.kind, &tech.evidence],
+            )?;
+        }
+        
+        for commit in &self.current_session.commits {
+            conn.execute(
+                "INSERT OR IGNORE INTO git_commits 
+                 (session_id, hash, subject, files_changed, insertions, deletions, committed_at) 
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    session_id,
+                    &commit.hash,
+                    &commit.subject,
+                    commit.files_changed,
+                    commit.insertions,
+                    commit.deletions,
+                    commit.committed_at.to_rfc3339()
+                ],
+            )?;
+        }
+        
+        Ok(())
+    }
+    
+    /// The first snapshot of the session and when the session started.
+    fn git_baseline(&mut self) -> Result<(Option<GitSnapshot>, DateTime<Local>)> {
+        if self.test_mode {
+            let first = self.current_session.git_snapshots.first().cloned();
+            return Ok((first, self.current_session.start_time));
+        }
+        
+        let session_id = self.get_or_create_session()?;
+        let conn = Connection::open(&self.db_path)?;
+        let start_time: String = conn.query_row(
+            "SELECT start_time FROM sessions WHERE id = ?1",
+            params![session_id],
+            |row| row.get(0),
+        )?;
+        let start_time = DateTime::parse_from_rfc3339(&start_time)?.with_timezone(&Local);
+        
+        // Snapshots taken by this process may not have been saved yet
+        let baseline = self.load_git_snapshots(&conn, session_id)?
+            .into_iter()
+            .next()
+            .or_else(|| self.current_session.git_snapshots.first().cloned());
+        Ok((baseline, start_time))
+    }
+    
+    fn ensure_git_baseline(&mut self) -> Result<()> {
+        if self.cwd.is_none() || !self.current_session.git_snapshots.is_empty() {
+            return Ok(());
+        }
+        if self.git_baseline()?.0.is_none() {
+            self.capture_git_activity()?;
+        }
+        Ok(())
+    }
+    
+    fn capture_git_activity(&mut self) -> Result<()> {
+        let Some(cwd) = self.cwd.clone() else {
+            return Ok(());
+        };
+        let Some(snapshot) = git::snapshot(Path::new(&cwd)) else {
+            return Ok(());
+        };
+        
+        let (baseline, start_time) = self.git_baseline()?;
+        if let Some(baseline) = baseline {
+            for commit in git::commits_since(Path::new(&cwd), baseline.head.as_deref(), start_time) {
+                if !self.current_session.commits.iter().any(|c| c.hash == commit.hash) {
+                    self.current_session.commits.push(commit);
+                }
             }
         }
         
-        // If no specific pattern matched, create a generic accomplishment for non-trivial prompts
-        if !found_accomplishment && prompt.len() > 20 {
-            let accomplishment = Accomplishment {
+        if self.verbose {
+            eprintln!(
+                "Git snapshot for {}: {:?} @ {:?}{}",
+                cwd,
+                snapshot.branch,
+                snapshot.head,
+                if snapshot.dirty { " (dirty)" } else { "" }
+            );
+        }
+        if let Some(branch) = &snapshot.branch {
+            let found = references::extract(branch, "branch", &self.config);
+            self.add_session_references(found);
+        }
+        self.current_session.git_snapshots.push(snapshot);
+        Ok(())
+    }
+    
+    fn add_session_references(&mut self, found: Vec<Reference>) {
+        for reference in found {
+            if !self.current_session.references.contains(&reference) {
+                self.current_session.references.push(reference);
+            }
+        }
+    }
+
+    fn infer_objectives_and_accomplishments(&mut self, event: &ClaudeEvent) {
+        if let Some(prompt) = &event.user_prompt {
+            // Try to parse the prompt as JSON first
+            let actual_prompt = if prompt.starts_with('{') && prompt.ends_with('}') {
+                // Attempt to parse as JSON to extract the actual prompt
+                if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(prompt) {
+                    // Try different possible field names for the actual prompt
+                    json_val.get("prompt")
+                        .or_else(|| json_val.get("user_prompt"))
+                        .or_else(|| json_val.get("message"))
+                        .or_else(|| json_val.get("text"))
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string())
+                        .unwrap_or_else(|| prompt.clone())
+                } else {
+                    prompt.clone()
+                }
+            } else {
+                prompt.clone()
+            };
+            
+            // Pasted code, logs and quoted output say nothing about what was asked for
+            let cleaned = prompt_clean::clean(&actual_prompt);
+            if self.verbose && !cleaned.removed.is_empty() {
+                eprintln!("Removed from prompt: {}", cleaned.removed.join(", "));
+            }
+            let actual_prompt = cleaned.intent;
+            if self.verbose {
+                eprintln!("Prompt language: {}", keywords::detect_language(&actual_prompt, &self.keyword_packs));
+            }
+            
+            // Extract objectives from user prompts
+            let Some(objective) = prompt_clean::objective(&actual_prompt, 100) else {
+                return;
+            };
+            
+            self.current_session.objectives.push((objective, event.occurred_at()));
+            
+            // Infer accomplishments from user prompts
+            let accomplishments_before = self.current_session.accomplishments.len();
+            self.infer_accomplishments_from_prompt(&actual_prompt, event.duration_ms, event.occurred_at());
+            
+            // Tie any ticket or issue references to the accomplishment they came with
+            let found = references::extract(&actual_prompt, "prompt", &self.config);
+            if !found.is_empty() {
+                if self.current_session.accomplishments.len() > accomplishments_before {
+                    if let Some(accomplishment) = self.current_session.accomplishments.last_mut() {
+                        accomplishment.references = found;
+                    }
+                } else {
+                    self.add_session_references(found);
+                }
+            }
+        }
+    }
+
+    fn infer_accomplishments_from_prompt(&mut self, prompt: &str, duration_ms: Option<u64>, recorded_at: DateTime<Local>) {
+        // Only create one accomplishment per prompt to avoid duplicates
+        let accomplishment = if let Some((category, default_description)) = keywords::classify(prompt, &self.keyword_packs) {
+            Accomplishment {
+                category: category.to_string(),
+                description: self.generate_accomplishment_description(prompt, default_description),
+                duration_ms,
+                files_affected: self.extract_files_from_prompt(prompt),
+                references: Vec::new(),
+                source: "prompt".to_string(),
+                status: status::REQUESTED.to_string(),
+                recorded_at,
+            }
+        } else if prompt.chars().count() > 20 {
+            // If no specific pattern matched, create a generic accomplishment for non-trivial prompts
+            Accomplishment {
                 category: "General".to_string(),
                 description: self.generate_accomplishment_description(prompt, "Worked on project task"),
                 duration_ms,
                 files_affected: self.extract_files_from_prompt(prompt),
-            };
-            
-            self.current_session.accomplishments.push(accomplishment);
-        }
+                references: Vec::new(),
+                source: "prompt".to_string(),
+                status: status::REQUESTED.to_string(),
+                recorded_at,
+            }
+        } else {
+            return;
+        };
+        
+        self.current_session.accomplishments.push(accomplishment);
     }
     
     fn generate_accomplishment_description(&self, prompt: &str, default: &str) -> String {
@@ -486,9 +1368,10 @@ impl DiaryManager {
             .unwrap_or(prompt)
             .trim();
             
-        if cleaned_prompt.len() > 80 {
-            format!("{}: {}", default, &cleaned_prompt[..77].trim())
-        } else if cleaned_prompt.len() > 10 {
+        let length = cleaned_prompt.chars().count();
+        if length > 80 {
+            format!("{}: {}", default, text::truncate_chars(cleaned_prompt, 77).trim())
+        } else if length > 10 {
             format!("{}: {}", default, cleaned_prompt)
         } else {
             default.to_string()
@@ -496,55 +1379,98 @@ impl DiaryManager {
     }
     
     fn extract_files_from_prompt(&self, prompt: &str) -> Vec<String> {
-        let mut files = Vec::new();
-        
-        // Look for common file patterns in the prompt
-        let file_patterns = [
-            r"[\w/.-]+\.rs",      // Rust files
-            r"[\w/.-]+\.js",      // JavaScript files
-            r"[\w/.-]+\.ts",      // TypeScript files
-            r"[\w/.-]+\.py",      // Python files
-            r"[\w/.-]+\.go",      // Go files
-            r"[\w/.-]+\.java",    // Java files
-            r"[\w/.-]+\.cpp",     // C++ files
-            r"[\w/.-]+\.c",       // C files
-            r"[\w/.-]+\.h",       // Header files
-            r"[\w/.-]+\.json",    // JSON files
-            r"[\w/.-]+\.yaml",    // YAML files
-            r"[\w/.-]+\.yml",     // YAML files
-            r"[\w/.-]+\.toml",    // TOML files
-            r"[\w/.-]+\.md",      // Markdown files
-        ];
-        
-        for pattern in &file_patterns {
-            if let Ok(regex) = regex::Regex::new(pattern) {
-                for mat in regex.find_iter(prompt) {
-                    files.push(mat.as_str().to_string());
-                }
-            }
-        }
-        
-        files.sort();
-        files.dedup();
-        files
+        file_refs::extract(prompt)
     }
 
     fn process_tool_activity(&mut self, event: &ClaudeEvent) -> Result<()> {
         if let Some(tool_calls) = &event.tool_calls {
             for tool_call in tool_calls {
+                // Test runner output carries pass/fail counts worth keeping, and a
+                // run with failing tests exits non-zero, so check before failures.
+                // Only commands that ran tests count; a file read can look like test output
+                let command = tool_call.parameters.as_ref()
+                    .and_then(|p| p.get("command"))
+                    .and_then(|c| c.as_str())
+                    .filter(|_| tool_call.tool_name == "Bash");
+                if let (Some(command), Some(output)) = (command, &tool_call.result) {
+                    if let Some(mut run) = test_runs::parse_test_output(command, output) {
+                        run.run_at = event.occurred_at();
+                        if self.verbose {
+                            eprintln!("Recorded test run: {}", run.summary());
+                        }
+                        if run.is_green() {
+                            self.apply_evidence(Evidence::TestsPassed)?;
+                        }
+                        self.current_session.test_runs.push(run);
+                    }
+                }
+                
+                // A failed call changed nothing, so it is an issue rather than an accomplishment
+                if tool_call.success == Some(false) {
+                    let file_path = tool_call.parameters.as_ref()
+                        .and_then(|p| p.get("file_path").or_else(|| p.get("notebook_path")))
+                        .and_then(|p| p.as_str())
+                        .map(|p| p.to_string());
+                    let error = tool_failures::error_text(tool_call.response.as_ref(), tool_call.result.as_deref());
+                    let failure = ToolFailure {
+                        tool_name: tool_call.tool_name.clone(),
+                        file_path,
+                        excerpt: tool_failures::excerpt(&error),
+                        failed_at: event.occurred_at(),
+                    };
+                    if self.verbose {
+                        eprintln!("Recorded tool failure: {}", failure.issue_text());
+                    }
+                    self.current_session.tool_failures.push(failure);
+                    continue;
+                }
+                
+                // Only a call that went through shows the work is under way
+                self.apply_evidence(Evidence::ToolActivity)?;
+                
+                // A task list is recorded item by item rather than as a tool use
+                if tool_call.tool_name == "TodoWrite" {
+                    if let Some(params) = &tool_call.parameters {
+                        self.process_todo_write(params, event.occurred_at())?;
+                    }
+                    continue;
+                }
+                
                 let category = self.categorize_tool(&tool_call.tool_name);
                 
-                let mut description = format!("Used {} tool", tool_call.tool_name);
+                let mut description = format!("Used {} tool", tools::display_name(&tool_call.tool_name));
                 let mut files_affected = Vec::new();
                 
-                // Extract file information from tool parameters
+                // Record which files the tool read, searched or changed
                 if let Some(params) = &tool_call.parameters {
-                    if let Some(file_path) = params.get("file_path") {
-                        if let Some(path_str) = file_path.as_str() {
-                            files_affected.push(path_str.to_string());
-                            self.current_session.files_modified.push(path_str.to_string());
-                            description = format!("Modified {}", path_str);
+                    self.detect_technologies(&tool_call.tool_name, params);
+                    
+                    let accesses = file_access::from_tool_call(&tool_call.tool_name, params, tool_call.response.as_ref());
+                    for (path, access_type) in accesses {
+                        description = match access_type {
+                            file_access::READ => format!("Read {}", path),
+                            file_access::CREATED => format!("Created {}", path),
+                            file_access::DELETED => format!("Deleted {}", path),
+                            file_access::SEARCHED => format!("Searched {}", path),
+                            _ => format!("Modified {}", path),
+                        };
+                        
+                        if file_access::is_modification(access_type) {
+                            files_affected.push(path.clone());
+                            self.current_session.files_modified.push(path.clone());
+                            
+                            if let Some(churn) = churn::from_tool_input(&tool_call.tool_name, params) {
+                                self.current_session.file_churn.push((path.clone(), churn));
+                                self.apply_evidence(Evidence::FileEdited(path.clone()))?;
+                            }
                         }
+                        
+                        self.current_session.file_accesses.push(FileAccess {
+                            path,
+                            access_type: access_type.to_string(),
+                            tool_name: tool_call.tool_name.clone(),
+                            accessed_at: event.occurred_at(),
+                        });
                     }
                 }
 
@@ -553,298 +1479,1728 @@ impl DiaryManager {
                     description,
                     duration_ms: tool_call.duration_ms,
                     files_affected,
+                    references: Vec::new(),
+                    source: "tool".to_string(),
+                    status: status::CONFIRMED.to_string(),
+                    recorded_at: event.occurred_at(),
+                };
+
+                self.current_session.accomplishments.push(accomplishment);
+            }
+        }
+        Ok(())
+    }
+
+    /// Note the languages, frameworks and tools a successful tool call shows
+    /// the session working with.
+    fn detect_technologies(&mut self, tool_name: &str, params: &serde_json::Value) {
+        let str_field = |key: &str| params.get(key).and_then(|v| v.as_str());
+        let mut found = Vec::new();
+        
+        if tool_name == "Bash" {
+            if let Some(command) = str_field("command") {
+                found.extend(tech_stack::from_command(command));
+            }
+        }
+        if let Some(path) = str_field("file_path").or_else(|| str_field("notebook_path")) {
+            found.extend(tech_stack::from_path(path));
+            if let Some(content) = str_field("content").or_else(|| str_field("new_string")) {
+                found.extend(tech_stack::from_manifest_content(path, content));
+            }
+        }
+        
+        tech_stack::merge(&mut self.current_session.technologies, found);
+    }
+    
+    /// Move accomplishments in this session along according to new evidence,
+    /// both those held in memory and those an earlier event already saved.
+    fn apply_evidence(&mut self, evidence: Evidence) -> Result<()> {
+        for acc in &mut self.current_session.accomplishments {
+            if let Some(next) = evidence.advance(&acc.status, &acc.category, &acc.files_affected) {
+                acc.status = next.to_string();
+            }
+        }
+        
+        if self.test_mode {
+            return Ok(());
+        }
+        
+        let session_id = self.get_or_create_session()?;
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare(
+            "SELECT id, status, category FROM accomplishments 
+             WHERE session_id = ?1 AND status IN (?2, ?3)"
+        )?;
+        let open = stmt.query_map(
+            params![session_id, status::REQUESTED, status::IN_PROGRESS],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)),
+        )?.collect::<Result<Vec<_>, _>>()?;
+        
+        let mut files_stmt = conn.prepare(
+            "SELECT file_path FROM accomplishment_files WHERE accomplishment_id = ?1"
+        )?;
+        for (acc_id, current, category) in open {
+            let files = files_stmt
+                .query_map([acc_id], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            if let Some(next) = evidence.advance(&current, &category, &files) {
+                conn.execute(
+                    "UPDATE accomplishments SET status = ?1 WHERE id = ?2",
+                    params![next, acc_id],
+                )?;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Record status changes in a `TodoWrite` task list and turn newly
+    /// completed items into accomplishments.
+    fn process_todo_write(&mut self, params: &serde_json::Value, changed_at: DateTime<Local>) -> Result<()> {
+        for item in todos::parse_todo_write(params) {
+            let previous = self.previous_todo_status(&item.content)?;
+            if previous.as_deref() == Some(item.status.as_str()) {
+                continue;
+            }
+            
+            if item.status == todos::COMPLETED {
+                let (category, _) = keywords::classify(&item.content, &self.keyword_packs).unwrap_or(("General", ""));
+                let accomplishment = Accomplishment {
+                    category: category.to_string(),
+                    description: item.content.clone(),
+                    duration_ms: None,
+                    files_affected: self.extract_files_from_prompt(&item.content),
+                    references: references::extract(&item.content, "todo", &self.config),
+                    source: "todo".to_string(),
+                    status: status::CONFIRMED.to_string(),
+                    recorded_at: changed_at,
                 };
+                self.current_session.accomplishments.push(accomplishment);
+            }
+            
+            self.current_session.todo_transitions.push(TodoTransition {
+                content: item.content.clone(),
+                from: previous,
+                to: item.status.clone(),
+                changed_at,
+            });
+            
+            match self.current_session.todos.iter_mut().find(|t| t.content == item.content) {
+                Some(existing) => existing.status = item.status,
+                None => self.current_session.todos.push(item),
+            }
+        }
+        Ok(())
+    }
+    
+    fn previous_todo_status(&mut self, content: &str) -> Result<Option<String>> {
+        if let Some(todo) = self.current_session.todos.iter().find(|t| t.content == content) {
+            return Ok(Some(todo.status.clone()));
+        }
+        if self.test_mode {
+            return Ok(None);
+        }
+        
+        let session_id = self.get_or_create_session()?;
+        let conn = Connection::open(&self.db_path)?;
+        let status = conn.query_row(
+            "SELECT status FROM todos WHERE session_id = ?1 AND content = ?2",
+            params![session_id, content],
+            |row| row.get(0),
+        ).ok();
+        Ok(status)
+    }
+
+    fn process_slash_command(&mut self, event: &ClaudeEvent) {
+        if let Some(command) = event.user_prompt.as_deref().and_then(prompt_clean::slash_command) {
+            if self.verbose {
+                eprintln!("Slash command: /{}", command.name);
+            }
+            self.current_session.slash_commands.push((command, event.occurred_at()));
+        }
+    }
+
+    fn process_error(&mut self, event: &ClaudeEvent) {
+        if let Some(error_msg) = &event.error {
+            let issue = format!("Error encountered: {}", 
+                if error_msg.chars().count() > 150 {
+                    format!("{}...", text::truncate_chars(error_msg, 150))
+                } else {
+                    error_msg.clone()
+                }
+            );
+            self.current_session.issues.push((issue, event.occurred_at()));
+        }
+    }
 
+    fn process_generic_activity(&mut self, event: &ClaudeEvent) {
+        // Claude Code's Stop hook only points at the transcript, so read the reply from there
+        let response = event.assistant_response.clone().or_else(|| {
+            event.transcript_path.as_deref().and_then(|path| summarize::last_assistant_text(Path::new(path)))
+        });
+        
+        if let Some(response) = response {
+            if response.chars().count() > 50 {
+                let Some(summary) = summarize::summarize(&response) else {
+                    return;
+                };
+                let category = keywords::classify(&summary, &self.keyword_packs).map(|(category, _)| category).unwrap_or("Analysis");
+                let accomplishment = Accomplishment {
+                    category: category.to_string(),
+                    description: summary,
+                    duration_ms: event.duration_ms,
+                    files_affected: Vec::new(),
+                    references: Vec::new(),
+                    source: "response".to_string(),
+                    status: status::CONFIRMED.to_string(),
+                    recorded_at: event.occurred_at(),
+                };
                 self.current_session.accomplishments.push(accomplishment);
             }
         }
+    }
+
+    /// Active time and the number of active segments for a session's events.
+    fn active_time(&self, events: &[ActivityEvent]) -> (u64, usize) {
+        let segments = activity::segments(events, self.config.idle_gap());
+        (activity::active_ms(&segments) as u64, segments.len())
+    }
+    
+    fn load_session_events(&self, conn: &Connection, session_id: i64) -> Result<Vec<ActivityEvent>> {
+        let mut stmt = conn.prepare(
+            "SELECT event_type, occurred_at, duration_ms FROM session_events WHERE session_id = ?1 ORDER BY id"
+        )?;
+        let rows = stmt.query_map([session_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+            ))
+        })?;
+        
+        let mut events = Vec::new();
+        for row in rows {
+            let (event_type, occurred_at, duration_ms) = row?;
+            events.push(ActivityEvent {
+                event_type,
+                occurred_at: DateTime::parse_from_rfc3339(&occurred_at)?.with_timezone(&Local),
+                duration_ms: duration_ms.map(|d| d as u64),
+            });
+        }
+        Ok(events)
+    }
+
+    fn categorize_tool(&self, tool_name: &str) -> String {
+        tools::categorize(tool_name, &self.config.tool_categories)
+    }
+
+    fn save_session_to_db(&mut self) -> Result<()> {
+        if self.test_mode {
+            (self.current_session.total_duration_ms, self.current_session.active_segments) =
+                self.active_time(&self.current_session.events);
+            let content = self.generate_diary_content();
+            let today = Local::now().format("%Y-%m-%d").to_string();
+            println!("=== DIARY ENTRY FOR {} ===", today);
+            println!("{}", content);
+            return Ok(());
+        }
+        
+        self.save_current_data()?;
+        let session_id = self.get_or_create_session()?;
+        let conn = Connection::open(&self.db_path)?;
+        
+        // Update session end time
+        conn.execute(
+            "UPDATE sessions SET end_time = ?1, total_duration_ms = ?2 WHERE id = ?3",
+            params![
+                self.current_session.end_time.map(|t| t.to_rfc3339()),
+                self.current_session.total_duration_ms as i64,
+                session_id
+            ],
+        )?;
+        
+        self.update_narratives(&conn, session_id)?;
+        
+        if self.verbose {
+            eprintln!("Saved session {} to database: {:?}", session_id, self.db_path);
+        }
+        
+        Ok(())
+    }
+
+    fn generate_diary_content(&self) -> String {
+        let mut content = String::new();
+        
+        let duration_display = format_active_time(
+            self.current_session.total_duration_ms as i64,
+            self.current_session.active_segments as i64,
+        );
+        
+        content.push_str(&format!("\n{}\n", narrative::session_narrative(&self.current_session_facts())));
+
+        // Completed todos say what was actually done, so they replace
+        // accomplishments guessed from prompt keywords
+        let has_todos = self.current_session.accomplishments.iter().any(|acc| acc.source == "todo");
+        
+        // Group accomplishments by category, leaving out unconfirmed work unless asked
+        let mut categories: HashMap<String, Vec<&Accomplishment>> = HashMap::new();
+        let mut hidden = 0;
+        for acc in &self.current_session.accomplishments {
+            if has_todos && acc.source == "prompt" {
+                continue;
+            }
+            if acc.status != status::CONFIRMED && !self.include_unconfirmed {
+                hidden += 1;
+                continue;
+            }
+            categories.entry(acc.category.clone()).or_default().push(acc);
+        }
+
+        content.push_str(&format!("\n### ✅ **Accomplishments** _({})*\n\n", duration_display));
+
+        for (category, accomplishments) in categories {
+            content.push_str(&format!("#### **{}**\n", category));
+            for acc in accomplishments {
+                let duration_str = if let Some(duration) = acc.duration_ms {
+                    format!(" _({}ms)_", duration)
+                } else {
+                    String::new()
+                };
+                content.push_str(&format!("- **{}**{}{}\n", acc.description, duration_str, status_label(&acc.status)));
+                
+                if !acc.files_affected.is_empty() {
+                    let cwd = self.cwd.as_deref().map(Path::new);
+                    let files: Vec<String> = acc.files_affected.iter()
+                        .map(|file| match file_refs::resolve(file, cwd) {
+                            (_, Some(false)) if acc.source == "prompt" => format!("{} (not found)", file),
+                            _ => file.clone(),
+                        })
+                        .collect();
+                    content.push_str("  - Files: ");
+                    content.push_str(&files.join(", "));
+                    content.push('\n');
+                }
+            }
+            content.push('\n');
+        }
+        
+        if let Some(hint) = unconfirmed_hint(hidden) {
+            content.push_str(&format!("{}\n\n", hint));
+        }
+
+        if !self.current_session.objectives.is_empty() {
+            content.push_str("### 🎯 **Session Objectives**\n");
+            for (obj, _) in &self.current_session.objectives {
+                content.push_str(&format!("- {}\n", obj));
+            }
+            content.push('\n');
+        }
+
+        if !self.current_session.issues.is_empty() || !self.current_session.tool_failures.is_empty() {
+            content.push_str("### ⚠️ **Issues Encountered**\n");
+            for (issue, _) in &self.current_session.issues {
+                content.push_str(&format!("- {}\n", issue));
+            }
+            for failure in &self.current_session.tool_failures {
+                content.push_str(&format!("- {}\n", failure.issue_text()));
+            }
+            content.push('\n');
+        }
+
+        content.push_str(&tech_stack::format_section(&self.current_session.technologies));
+        let commands: Vec<SlashCommand> = self.current_session.slash_commands.iter().map(|(c, _)| c.clone()).collect();
+        content.push_str(&prompt_clean::format_section(&commands));
+        content.push_str(&todos::format_section(&self.current_session.todos));
+        content.push_str(&test_runs::format_section(&self.current_session.test_runs));
+        content.push_str(&git::format_section(
+            self.current_session.git_snapshots.last(),
+            &self.current_session.commits,
+        ));
+
+        if !self.current_session.tool_usage.is_empty() {
+            content.push_str("### 🛠 **Tools Used**\n");
+            for (tool, count) in &self.current_session.tool_usage {
+                let failed = self.current_session.tool_failures.iter().filter(|f| &f.tool_name == tool).count();
+                if failed > 0 {
+                    content.push_str(&format!("- {}: {} times ({} failed)\n", tool, count, failed));
+                } else {
+                    content.push_str(&format!("- {}: {} times\n", tool, count));
+                }
+            }
+            content.push('\n');
+        }
+
+        let mut unique_files: Vec<_> = self.current_session.files_modified.iter().collect();
+        unique_files.sort();
+        unique_files.dedup();
+        let files: Vec<(String, LineChurn)> = unique_files
+            .into_iter()
+            .map(|file| {
+                let mut total = LineChurn::default();
+                for (_, churn) in self.current_session.file_churn.iter().filter(|(path, _)| path == file) {
+                    total += *churn;
+                }
+                (file.clone(), total)
+            })
+            .collect();
+        content.push_str(&churn::format_section(&files));
+        content.push_str(&file_access::format_section(&file_access::explored(&self.current_session.file_accesses)));
+
+        content.push_str("---\n");
+        
+        content
+    }
+    
+    
+    /// The narrative facts for the session held in memory, used in test mode.
+    fn current_session_facts(&self) -> SessionFacts {
+        let session = &self.current_session;
+        let (files_edited, files_created, files_deleted, files_explored) =
+            file_access::count_changes(&session.file_accesses);
+        
+        // A failure on a file is resolved once that file is changed afterwards
+        let unresolved_failures = session.tool_failures.iter()
+            .filter(|failure| match &failure.file_path {
+                Some(path) => !session.file_accesses.iter().any(|a| {
+                    a.is_modification() && &a.path == path && a.accessed_at >= failure.failed_at
+                }),
+                None => true,
+            })
+            .count();
+        
+        let all_references: Vec<Reference> = session.accomplishments.iter()
+            .flat_map(|acc| acc.references.iter().cloned())
+            .chain(session.references.iter().cloned())
+            .collect();
+        
+        SessionFacts {
+            project: project_name(self.cwd.as_deref()),
+            topic: session_topic(&all_references, session.objectives.first().map(|(objective, _)| objective)),
+            files_edited,
+            files_created,
+            files_deleted,
+            files_explored,
+            test_runs: session.test_runs.len(),
+            final_run_green: session.test_runs.last().map(|run| run.is_green()),
+            final_run_failed: session.test_runs.last().map(|run| run.failed).unwrap_or(0),
+            commits: session.commits.len(),
+            unresolved_errors: session.issues.len() + unresolved_failures,
+            duration_ms: session.total_duration_ms as i64,
+        }
+    }
+    
+    fn load_session_facts(&self, conn: &Connection, session_id: i64) -> Result<SessionFacts> {
+        let (cwd, duration_ms): (Option<String>, i64) = conn.query_row(
+            "SELECT cwd, total_duration_ms FROM sessions WHERE id = ?1",
+            [session_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let first_objective: Option<String> = conn.query_row(
+            "SELECT objective FROM objectives WHERE session_id = ?1 ORDER BY id LIMIT 1",
+            [session_id],
+            |row| row.get(0),
+        ).ok();
+        let (files_edited, files_created, files_deleted, files_explored) =
+            file_access::count_changes(&self.load_file_accesses(conn, session_id)?);
+        let runs = self.load_test_runs(conn, session_id)?;
+        
+        let unresolved_errors = self.load_unresolved_issues(conn, session_id)?.len();
+        
+        Ok(SessionFacts {
+            project: project_name(cwd.as_deref()),
+            topic: session_topic(&self.load_references(conn, session_id)?, first_objective.as_ref()),
+            files_edited,
+            files_created,
+            files_deleted,
+            files_explored,
+            test_runs: runs.len(),
+            final_run_green: runs.last().map(|run| run.is_green()),
+            final_run_failed: runs.last().map(|run| run.failed).unwrap_or(0),
+            commits: self.load_git_commits(conn, session_id)?.len(),
+            unresolved_errors,
+            duration_ms,
+        })
+    }
+    
+    /// A session's issues that were not resolved, as (issue, created_at).
+    /// Failures on a file that was changed afterwards count as resolved.
+    fn load_unresolved_issues(&self, conn: &Connection, session_id: i64) -> Result<Vec<(String, Option<String>)>> {
+        let mut stmt = conn.prepare(
+            "SELECT i.issue, i.created_at FROM issues i WHERE i.session_id = ?1 AND NOT (
+                 i.file_path IS NOT NULL AND EXISTS (
+                     SELECT 1 FROM file_access a 
+                     WHERE a.session_id = i.session_id AND a.file_path = i.file_path 
+                       AND a.access_type IN ('created', 'edited', 'deleted') AND a.accessed_at >= i.created_at))
+             ORDER BY i.id"
+        )?;
+        let issues = stmt.query_map([session_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(issues.collect::<Result<Vec<_>, _>>()?)
+    }
+    
+    /// Rewrite the stored narrative of a session and the roll-up of the day it started on.
+    fn update_narratives(&self, conn: &Connection, session_id: i64) -> Result<()> {
+        let facts = self.load_session_facts(conn, session_id)?;
+        conn.execute(
+            "UPDATE sessions SET narrative = ?1 WHERE id = ?2",
+            params![narrative::session_narrative(&facts), session_id],
+        )?;
+        
+        let start_time: String = conn.query_row(
+            "SELECT start_time FROM sessions WHERE id = ?1",
+            [session_id],
+            |row| row.get(0),
+        )?;
+        let day = DateTime::parse_from_rfc3339(&start_time)?.with_timezone(&Local).date_naive();
+        
+        let mut day_facts = Vec::new();
+        for session in self.sessions_started_in(conn, &DateRange::day(day))? {
+            day_facts.push(self.load_session_facts(conn, session.id)?);
+        }
+        let total_ms: i64 = day_facts.iter().map(|f| f.duration_ms).sum();
+        conn.execute(
+            "INSERT INTO daily_summaries (day, narrative, session_count, updated_at) VALUES (?1, ?2, ?3, ?4) 
+             ON CONFLICT (day) DO UPDATE SET 
+                 narrative = excluded.narrative, session_count = excluded.session_count, updated_at = excluded.updated_at",
+            params![
+                day.to_string(),
+                narrative::day_narrative(&day_facts, &format_duration(total_ms)),
+                day_facts.len(),
+                Local::now().to_rfc3339()
+            ],
+        )?;
+        Ok(())
+    }
+    
+    /// Sessions that started in `range` (local days), oldest first.
+    fn sessions_started_in(&self, conn: &Connection, range: &DateRange) -> Result<Vec<StoredSession>> {
+        self.sessions_where(conn, |session| range.contains(session.day))
+    }
+    
+    /// Sessions that started in `range` or recorded accomplishments or issues
+    /// in it, oldest first.
+    fn sessions_recorded_in(&self, conn: &Connection, range: &DateRange) -> Result<Vec<StoredSession>> {
+        let active = self.sessions_active_in(conn, *range)?;
+        self.sessions_where(conn, |session| range.contains(session.day) || active.contains(&session.id))
+    }
+    
+    fn sessions_where(&self, conn: &Connection, keep: impl Fn(&StoredSession) -> bool) -> Result<Vec<StoredSession>> {
+        let mut stmt = conn.prepare("SELECT id, start_time, total_duration_ms, cwd FROM sessions ORDER BY start_time")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?;
+        
+        let mut sessions = Vec::new();
+        for row in rows {
+            let (id, start_time, active_ms, cwd) = row?;
+            let Some(day) = dates::local_day(&start_time) else {
+                continue;
+            };
+            let session = StoredSession { id, day, active_ms, cwd };
+            if keep(&session) {
+                sessions.push(session);
+            }
+        }
+        Ok(sessions)
+    }
+    
+    fn load_test_runs(&self, conn: &Connection, session_id: i64) -> Result<Vec<TestRun>> {
+        let mut stmt = conn.prepare(
+            "SELECT runner, passed, failed, skipped, failing_tests, duration_ms, run_at 
+             FROM test_runs WHERE session_id = ?1 ORDER BY run_at, id"
+        )?;
+        
+        let rows = stmt.query_map([session_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, u32>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, u32>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<i64>>(5)?,
+                row.get::<_, String>(6)?,
+            ))
+        })?;
+        
+        let mut runs = Vec::new();
+        for row in rows {
+            let (runner, passed, failed, skipped, failing_tests, duration_ms, run_at) = row?;
+            runs.push(TestRun {
+                runner,
+                passed,
+                failed,
+                skipped,
+                failing_tests: failing_tests
+                    .and_then(|json| serde_json::from_str(&json).ok())
+                    .unwrap_or_default(),
+                duration_ms: duration_ms.map(|d| d as u64),
+                run_at: DateTime::parse_from_rfc3339(&run_at)?.with_timezone(&Local),
+            });
+        }
+        
+        Ok(runs)
+    }
+    
+    fn load_git_snapshots(&self, conn: &Connection, session_id: i64) -> Result<Vec<GitSnapshot>> {
+        let mut stmt = conn.prepare(
+            "SELECT head, branch, dirty, taken_at FROM git_snapshots WHERE session_id = ?1 ORDER BY id"
+        )?;
+        
+        let rows = stmt.query_map([session_id], |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, bool>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+        
+        let mut snapshots = Vec::new();
+        for row in rows {
+            let (head, branch, dirty, taken_at) = row?;
+            snapshots.push(GitSnapshot {
+                head,
+                branch,
+                dirty,
+                taken_at: DateTime::parse_from_rfc3339(&taken_at)?.with_timezone(&Local),
+            });
+        }
+        
+        Ok(snapshots)
+    }
+    
+    fn load_git_commits(&self, conn: &Connection, session_id: i64) -> Result<Vec<GitCommit>> {
+        let mut stmt = conn.prepare(
+            "SELECT hash, subject, files_changed, insertions, deletions, committed_at 
+             FROM git_commits WHERE session_id = ?1 ORDER BY committed_at, id"
+        )?;
+        
+        let rows = stmt.query_map([session_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, u32>(3)?,
+                row.get::<_, u32>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })?;
+        
+        let mut commits = Vec::new();
+        for row in rows {
+            let (hash, subject, files_changed, insertions, deletions, committed_at) = row?;
+            commits.push(GitCommit {
+                hash,
+                subject,
+                files_changed,
+                insertions,
+                deletions,
+                committed_at: DateTime::parse_from_rfc3339(&committed_at)?.with_timezone(&Local),
+            });
+        }
+        
+        Ok(commits)
+    }
+    
+    fn load_todos(&self, conn: &Connection, session_id: i64) -> Result<Vec<TodoItem>> {
+        let mut stmt = conn.prepare(
+            "SELECT content, status FROM todos WHERE session_id = ?1 ORDER BY id"
+        )?;
+        
+        let todo_items = stmt.query_map([session_id], |row| {
+            Ok(TodoItem {
+                content: row.get(0)?,
+                status: row.get(1)?,
+            })
+        })?;
+        
+        Ok(todo_items.collect::<Result<Vec<_>, _>>()?)
+    }
+    
+    fn load_files_modified(&self, conn: &Connection, session_id: i64) -> Result<Vec<(String, LineChurn)>> {
+        let mut stmt = conn.prepare(
+            "SELECT file_path, SUM(lines_added), SUM(lines_removed) FROM files_modified 
+             WHERE session_id = ?1 GROUP BY file_path ORDER BY file_path"
+        )?;
+        
+        let files = stmt.query_map([session_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                LineChurn {
+                    added: row.get(1)?,
+                    removed: row.get(2)?,
+                },
+            ))
+        })?;
+        
+        Ok(files.collect::<Result<Vec<_>, _>>()?)
+    }
+    
+    fn load_file_accesses(&self, conn: &Connection, session_id: i64) -> Result<Vec<FileAccess>> {
+        let mut stmt = conn.prepare(
+            "SELECT file_path, access_type, tool_name, accessed_at FROM file_access 
+             WHERE session_id = ?1 ORDER BY id"
+        )?;
+        
+        let rows = stmt.query_map([session_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+        
+        let mut accesses = Vec::new();
+        for row in rows {
+            let (path, access_type, tool_name, accessed_at) = row?;
+            accesses.push(FileAccess {
+                path,
+                access_type,
+                tool_name,
+                accessed_at: DateTime::parse_from_rfc3339(&accessed_at)?.with_timezone(&Local),
+            });
+        }
+        Ok(accesses)
+    }
+    
+    fn load_technologies(&self, conn: &Connection, session_id: i64) -> Result<Vec<Technology>> {
+        let mut stmt = conn.prepare(
+            "SELECT name, kind, evidence FROM session_technologies WHERE session_id = ?1 ORDER BY id"
+        )?;
+        
+        let technologies = stmt.query_map([session_id], |row| {
+            Ok(Technology {
+                name: row.get(0)?,
+                kind: row.get(1)?,
+                evidence: row.get(2)?,
+            })
+        })?;
+        
+        Ok(technologies.collect::<Result<Vec<_>, _>>()?)
+    }
+    
+    fn load_slash_commands(&self, conn: &Connection, session_id: i64) -> Result<Vec<SlashCommand>> {
+        let mut stmt = conn.prepare(
+            "SELECT name, args FROM slash_commands WHERE session_id = ?1 ORDER BY id"
+        )?;
+        
+        let commands = stmt.query_map([session_id], |row| {
+            Ok(SlashCommand {
+                name: row.get(0)?,
+                args: row.get(1)?,
+            })
+        })?;
+        
+        Ok(commands.collect::<Result<Vec<_>, _>>()?)
+    }
+    
+    fn load_references(&self, conn: &Connection, session_id: i64) -> Result<Vec<Reference>> {
+        let mut stmt = conn.prepare(
+            "SELECT kind, ref_key, source FROM `references` WHERE session_id = ?1
+             GROUP BY ref_key ORDER BY MIN(id)"
+        )?;
+
+        let references = stmt.query_map([session_id], |row| {
+            Ok(Reference {
+                kind: row.get(0)?,
+                key: row.get(1)?,
+                source: row.get(2)?,
+            })
+        })?;
+
+        Ok(references.collect::<Result<Vec<_>, _>>()?)
+    }
+    
+    /// Everything recorded in a session, for JSON output. Accomplishments and
+    /// issues are limited to those recorded in `range`, as in Markdown output.
+    fn load_session_record(&self, conn: &Connection, session_id: i64, range: DateRange) -> Result<output::SessionRecord> {
+        let timestamp = |ts: Option<String>| ts.map(|ts| dates::local_time(&ts).map(|t| t.to_rfc3339()).unwrap_or(ts));
+        
+        let mut record = conn.query_row(
+            "SELECT claude_session_id, start_time, end_time, total_duration_ms, active_segments, cwd, narrative 
+             FROM sessions WHERE id = ?1",
+            [session_id],
+            |row| {
+                Ok(output::SessionRecord {
+                    id: session_id,
+                    claude_session_id: row.get(0)?,
+                    start_time: row.get(1)?,
+                    end_time: row.get(2)?,
+                    active_ms: row.get(3)?,
+                    active_segments: row.get(4)?,
+                    cwd: row.get(5)?,
+                    narrative: row.get(6)?,
+                    objectives: Vec::new(),
+                    accomplishments: Vec::new(),
+                    issues: Vec::new(),
+                    tool_usage: Vec::new(),
+                    files_modified: Vec::new(),
+                })
+            },
+        )?;
+        
+        let mut stmt = conn.prepare("SELECT objective FROM objectives WHERE session_id = ?1 ORDER BY id")?;
+        record.objectives = stmt
+            .query_map([session_id], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, category, description, status, source, duration_ms, created_at FROM accomplishments 
+             WHERE session_id = ?1 AND {} ORDER BY id",
+            status::TODOS_REPLACE_PROMPTS_SQL
+        ))?;
+        let mut files_stmt = conn.prepare(
+            "SELECT COALESCE(resolved_path, file_path) FROM accomplishment_files WHERE accomplishment_id = ?1 ORDER BY id"
+        )?;
+        let rows = stmt.query_map([session_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<i64>>(5)?,
+                row.get::<_, Option<String>>(6)?,
+            ))
+        })?.collect::<Result<Vec<_>, _>>()?;
+        for (id, category, description, acc_status, source, duration_ms, created_at) in rows {
+            // Rows from before statuses existed count as confirmed
+            let acc_status = acc_status.unwrap_or_else(|| status::CONFIRMED.to_string());
+            if (acc_status != status::CONFIRMED && !self.include_unconfirmed) || !recorded_in(range, created_at.as_deref()) {
+                continue;
+            }
+            let files = files_stmt
+                .query_map([id], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            record.accomplishments.push(output::AccomplishmentRecord {
+                category,
+                description,
+                status: acc_status,
+                source,
+                duration_ms,
+                created_at: timestamp(created_at),
+                files,
+            });
+        }
+        
+        let mut stmt = conn.prepare(
+            "SELECT issue, tool_name, file_path, created_at FROM issues WHERE session_id = ?1 ORDER BY id"
+        )?;
+        let issues = stmt.query_map([session_id], |row| {
+            Ok(output::IssueRecord {
+                issue: row.get(0)?,
+                tool_name: row.get(1)?,
+                file_path: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+        record.issues = issues
+            .into_iter()
+            .filter(|issue| recorded_in(range, issue.created_at.as_deref()))
+            .map(|issue| output::IssueRecord { created_at: timestamp(issue.created_at), ..issue })
+            .collect();
+        
+        let mut stmt = conn.prepare(
+            "SELECT tool_name, SUM(usage_count), SUM(success_count), SUM(failure_count), MAX(mcp_server), MAX(mcp_tool) 
+             FROM tool_usage WHERE session_id = ?1 GROUP BY tool_name ORDER BY SUM(usage_count) DESC, tool_name"
+        )?;
+        record.tool_usage = stmt.query_map([session_id], |row| {
+            let tool_name: String = row.get(0)?;
+            Ok(output::ToolUsageRecord {
+                category: self.categorize_tool(&tool_name),
+                tool_name,
+                uses: row.get(1)?,
+                successes: row.get(2)?,
+                failures: row.get(3)?,
+                mcp_server: row.get(4)?,
+                mcp_tool: row.get(5)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+        
+        let mut stmt = conn.prepare(
+            "SELECT file_path, SUM(lines_added), SUM(lines_removed), SUM(edit_count) FROM files_modified 
+             WHERE session_id = ?1 GROUP BY file_path ORDER BY file_path"
+        )?;
+        record.files_modified = stmt.query_map([session_id], |row| {
+            Ok(output::FileRecord {
+                path: row.get(0)?,
+                lines_added: row.get(1)?,
+                lines_removed: row.get(2)?,
+                edits: row.get(3)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+        
+        Ok(record)
+    }
+
+    fn show_ticket(&self, key: &str) -> Result<()> {
+        if self.test_mode {
+            println!("Ticket lookup not available in test mode");
+            return Ok(());
+        }
+
+        let conn = Connection::open(&self.db_path)?;
+
+        // `#512` should also find issue and pull request URLs ending in /512
+        let url_suffix = key.strip_prefix('#').map(|number| format!("%/{}", number));
+        let matches_key = "(ref_key = ?1 COLLATE NOCASE OR (?2 IS NOT NULL AND ref_key LIKE ?2))";
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, start_time, total_duration_ms, cwd FROM sessions
+             WHERE id IN (SELECT session_id FROM `references` WHERE {})
+             ORDER BY start_time",
+            matches_key
+        ))?;
+
+        let sessions = stmt.query_map(params![key, url_suffix], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        if self.format != OutputFormat::Markdown {
+            let mut records = Vec::new();
+            for (session_id, ..) in &sessions {
+                records.push(self.load_session_record(&conn, *session_id, DateRange::default())?);
+            }
+            return Report::new("ticket")
+                .field("key", key)?
+                .field("active_ms", sessions.iter().map(|(_, _, duration, _)| duration).sum::<i64>())?
+                .records("sessions", "session", &records)?
+                .print(self.format);
+        }
+        
+        println!("\n=== TICKET {} ===", key);
+
+        if sessions.is_empty() {
+            println!("\nNo sessions reference {}", key);
+            return Ok(());
+        }
+
+        let total_ms: i64 = sessions.iter().map(|(_, _, duration, _)| duration).sum();
+        println!(
+            "\n**Total time:** {} across {} session{}",
+            format_duration(total_ms),
+            sessions.len(),
+            if sessions.len() == 1 { "" } else { "s" }
+        );
+
+        let mut acc_stmt = conn.prepare(&format!(
+            "SELECT category, description FROM accomplishments
+             WHERE session_id = ?3 AND id IN (SELECT accomplishment_id FROM `references` WHERE {})
+               AND (?4 OR {})
+             ORDER BY id",
+            matches_key,
+            status::CONFIRMED_SQL
+        ))?;
+
+        for (session_id, start_time, total_duration_ms, cwd) in sessions {
+            let start_dt = DateTime::parse_from_rfc3339(&start_time)?.with_timezone(&Local);
+            println!("\n## Session {} - {}",
+                start_dt.format("%Y-%m-%d %H:%M:%S"),
+                format_duration(total_duration_ms)
+            );
+            if let Some(cwd) = cwd {
+                println!("_{}_", cwd);
+            }
+
+            let accomplishments = acc_stmt.query_map(params![key, url_suffix, session_id, self.include_unconfirmed], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?;
+
+            for acc_result in accomplishments {
+                let (category, description) = acc_result?;
+                println!("- **{}** _({})_", description, category);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn show_tool_failures(&self) -> Result<()> {
+        if self.test_mode {
+            println!("Tool report not available in test mode");
+            return Ok(());
+        }
+        
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare(
+            "SELECT tool_name, SUM(usage_count), SUM(failure_count) FROM tool_usage 
+             GROUP BY tool_name 
+             ORDER BY SUM(failure_count) DESC, SUM(failure_count) * 1.0 / SUM(usage_count) DESC, SUM(usage_count) DESC"
+        )?;
+        let mut errors_stmt = conn.prepare(
+            "SELECT issue FROM issues WHERE tool_name = ?1 ORDER BY id DESC LIMIT 3"
+        )?;
+        
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?, row.get::<_, u32>(2)?))
+        })?.collect::<Result<Vec<_>, _>>()?;
+        
+        let mut stats = Vec::new();
+        for (tool_name, uses, failures) in rows {
+            let recent_errors = errors_stmt
+                .query_map([&tool_name], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            stats.push(ToolStats { tool_name, uses, failures, recent_errors });
+        }
+        
+        // Rows from before MCP columns existed are parsed from the tool name
+        let mut mcp_stmt = conn.prepare(
+            "SELECT tool_name, mcp_server, mcp_tool, SUM(usage_count), SUM(failure_count) FROM tool_usage 
+             WHERE tool_name LIKE 'mcp\\_\\_%' ESCAPE '\\' 
+             GROUP BY tool_name ORDER BY SUM(usage_count) DESC"
+        )?;
+        let mcp_rows = mcp_stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, u32>(3)?,
+                row.get::<_, u32>(4)?,
+            ))
+        })?.collect::<Result<Vec<_>, _>>()?;
+        
+        let mut servers: Vec<tools::McpServerStats> = Vec::new();
+        for (tool_name, server, tool, calls, failures) in mcp_rows {
+            let parsed = tools::parse_mcp(&tool_name);
+            let Some(server) = server.or_else(|| parsed.as_ref().map(|m| m.server.to_string())) else {
+                continue;
+            };
+            let tool = tool.or_else(|| parsed.as_ref().map(|m| m.tool.to_string())).unwrap_or_default();
+            let index = match servers.iter().position(|s| s.server == server) {
+                Some(index) => index,
+                None => {
+                    servers.push(tools::McpServerStats { server, calls: 0, failures: 0, tools: Vec::new() });
+                    servers.len() - 1
+                }
+            };
+            let entry = &mut servers[index];
+            entry.calls += calls;
+            entry.failures += failures;
+            entry.tools.push((tool, calls));
+        }
+        servers.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.server.cmp(&b.server)));
+        
+        if self.format != OutputFormat::Markdown {
+            let tool_records: Vec<output::ToolRecord> = stats
+                .iter()
+                .map(|tool| output::ToolRecord {
+                    tool_name: tool.tool_name.clone(),
+                    uses: tool.uses,
+                    failures: tool.failures,
+                    failure_rate: tool.failure_rate(),
+                    recent_errors: tool.recent_errors.clone(),
+                })
+                .collect();
+            let server_records: Vec<output::McpServerRecord> = servers
+                .iter()
+                .map(|server| output::McpServerRecord {
+                    server: server.server.clone(),
+                    calls: server.calls,
+                    failures: server.failures,
+                    tools: server
+                        .tools
+                        .iter()
+                        .map(|(tool, calls)| output::McpToolRecord { tool: tool.clone(), calls: *calls })
+                        .collect(),
+                })
+                .collect();
+            return Report::new("tools")
+                .records("tools", "tool", &tool_records)?
+                .records("mcp_servers", "mcp_server", &server_records)?
+                .print(self.format);
+        }
+        
+        println!("\n=== TOOL RELIABILITY ===\n");
+        print!("{}", tool_failures::format_report(&stats));
+        print!("{}", tools::format_mcp_section(&servers));
+        Ok(())
+    }
+
+    /// Build the standup for `today` from the previous working day's sessions.
+    fn show_standup(&self, today: NaiveDate, plain: bool) -> Result<()> {
+        if self.test_mode {
+            println!("Standup not available in test mode");
+            return Ok(());
+        }
+        
+        let conn = Connection::open(&self.db_path)?;
+        let day = standup::previous_working_day(today);
+        let range = DateRange::day(day);
+        
+        let mut acc_stmt = conn.prepare(&format!(
+            "SELECT description, source, status, created_at FROM accomplishments 
+             WHERE session_id = ?1 AND {} ORDER BY id",
+            status::TODOS_REPLACE_PROMPTS_SQL
+        ))?;
+        // Objectives come from prompts, so todos replace them just the same
+        let mut objective_stmt = conn.prepare(&format!(
+            "SELECT objective, created_at FROM (
+                 SELECT id, objective, created_at, 'prompt' AS source FROM objectives WHERE session_id = ?1) 
+             WHERE {} ORDER BY id",
+            status::TODOS_REPLACE_PROMPTS_SQL
+        ))?;
+        
+        let mut projects: Vec<standup::ProjectStandup> = Vec::new();
+        for session in self.sessions_recorded_in(&conn, &range)? {
+            let session_id = session.id;
+            let project = project_name(session.cwd.as_deref()).unwrap_or_else(|| "Other".to_string());
+            let index = match projects.iter().position(|p| p.project == project) {
+                Some(index) => index,
+                None => {
+                    projects.push(standup::ProjectStandup::new(&project));
+                    projects.len() - 1
+                }
+            };
+            let entry = &mut projects[index];
+            
+            let accomplishments = acc_stmt.query_map([session_id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            })?.collect::<Result<Vec<_>, _>>()?;
+            let mut requests = Vec::new();
+            for (description, source, acc_status, created_at) in accomplishments {
+                if !recorded_in(range, created_at.as_deref()) {
+                    continue;
+                }
+                let request = standup::request_text(&description).filter(|_| source.as_deref() == Some("prompt"));
+                if let Some(request) = request {
+                    requests.push(request.to_string());
+                }
+                // Rows from before statuses existed count as confirmed
+                match (acc_status.as_deref(), request) {
+                    (None | Some(status::CONFIRMED), _) => standup::push_unique(&mut entry.done, description),
+                    (Some(_), Some(request)) => standup::push_unique(&mut entry.planned, request.to_string()),
+                    (Some(_), None) => {}
+                }
+            }
+            
+            for todo in self.load_todos(&conn, session_id)? {
+                if todo.status != todos::COMPLETED {
+                    standup::push_unique(&mut entry.planned, todo.content);
+                }
+            }
+            
+            // Objectives a request accounts for are already done or planned. Of the
+            // rest, those the hook would have taken as a request are still open
+            let objectives = objective_stmt.query_map([session_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+            })?.collect::<Result<Vec<_>, _>>()?;
+            for (objective, created_at) in objectives {
+                let requested = requests.iter().any(|request| standup::objective_matches(&objective, request));
+                let is_work = keywords::classify(&objective, &self.keyword_packs).is_some() || objective.chars().count() > 20;
+                if recorded_in(range, created_at.as_deref()) && !requested && is_work {
+                    standup::push_unique(&mut entry.planned, objective);
+                }
+            }
+            
+            for (issue, created_at) in self.load_unresolved_issues(&conn, session_id)? {
+                if recorded_in(range, created_at.as_deref()) {
+                    standup::push_unique(&mut entry.blockers, issue);
+                }
+            }
+        }
+        projects.retain(|p| !p.done.is_empty() || !p.planned.is_empty() || !p.blockers.is_empty());
+        
+        if self.format != OutputFormat::Markdown {
+            return Report::new("standup")
+                .field("date", today)?
+                .field("previous_working_day", day)?
+                .records("projects", "standup_project", &projects)?
+                .print(self.format);
+        }
+        
+        if plain {
+            println!("Standup {}\n", today);
+        } else {
+            println!("## Standup {}\n", today);
+        }
+        print!("{}", standup::format(day, &projects, !plain));
+        Ok(())
+    }
+
+    /// Totals for the sessions that started in `range`.
+    fn period_stats(&self, conn: &Connection, range: DateRange) -> Result<period::PeriodStats> {
+        let mut stats = period::PeriodStats::new(range);
+        
+        let mut category_stmt = conn.prepare(&format!(
+            "SELECT category FROM accomplishments WHERE session_id = ?1 AND {} AND {}",
+            status::CONFIRMED_SQL,
+            status::TODOS_REPLACE_PROMPTS_SQL
+        ))?;
+        let mut files_stmt = conn.prepare(
+            "SELECT file_path, SUM(edit_count), SUM(lines_added + lines_removed) FROM files_modified 
+             WHERE session_id = ?1 GROUP BY file_path"
+        )?;
+        
+        for StoredSession { id: session_id, active_ms: duration_ms, cwd, .. } in self.sessions_started_in(conn, &range)? {
+            let categories = category_stmt
+                .query_map([session_id], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            let project = project_name(cwd.as_deref()).unwrap_or_else(|| "Other".to_string());
+            stats.add_session(&project, duration_ms, &categories);
+            
+            let files = files_stmt.query_map([session_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?))
+            })?;
+            for file in files {
+                let (path, edits, lines_changed) = file?;
+                stats.add_file(&path, edits, lines_changed);
+            }
+            
+            stats.commits += self.load_git_commits(conn, session_id)?.len();
+            let runs = self.load_test_runs(conn, session_id)?;
+            stats.test_runs += runs.len();
+            stats.green_test_runs += runs.iter().filter(|run| run.is_green()).count();
+        }
+        
+        stats.finish();
+        Ok(stats)
+    }
+    
+    /// The weekly or monthly report for the period containing `day`.
+    fn show_period_report(&self, day: NaiveDate, monthly: bool, html: Option<&Path>) -> Result<()> {
+        if self.test_mode {
+            println!("Reports not available in test mode");
+            return Ok(());
+        }
+        
+        let (label, range, previous_range) = if monthly {
+            let range = dates::month_of(day);
+            let previous = range.since.map(|since| dates::month_of(since - chrono::Duration::days(1))).unwrap_or_default();
+            ("Monthly", range, previous)
+        } else {
+            let range = dates::week_of(day);
+            let previous = range.since.map(|since| dates::week_of(since - chrono::Duration::days(7))).unwrap_or_default();
+            ("Weekly", range, previous)
+        };
+        
+        let conn = Connection::open(&self.db_path)?;
+        let current = self.period_stats(&conn, range)?;
+        let previous = self.period_stats(&conn, previous_range)?;
+        
+        if let Some(path) = html {
+            return self.write_html_report(&conn, label, &current, path);
+        }
+        
+        if self.format != OutputFormat::Markdown {
+            return Report::new("period_report")
+                .field("period", if monthly { "month" } else { "week" })?
+                .records("periods", "period_stats", &[current, previous])?
+                .print(self.format);
+        }
+        
+        print!("\n{}", period::format_report(label, &current, &previous));
         Ok(())
     }
 
-    fn process_error(&mut self, event: &ClaudeEvent) {
-        if let Some(error_msg) = &event.error {
-            let issue = format!("Error encountered: {}", 
-                if error_msg.len() > 150 {
-                    format!("{}...", error_msg.chars().take(150).collect::<String>())
-                } else {
-                    error_msg.clone()
+    /// Write the HTML version of a period report to `path`.
+    fn write_html_report(&self, conn: &Connection, label: &str, stats: &period::PeriodStats, path: &Path) -> Result<()> {
+        let range = DateRange { since: stats.since, until: stats.until };
+        let mut sessions = Vec::new();
+        let mut timeline = Vec::new();
+        let mut tools: Vec<(String, i64)> = Vec::new();
+        for session in self.sessions_started_in(conn, &range)? {
+            let record = self.load_session_record(conn, session.id, range)?;
+            timeline.push(html::TimelineSession {
+                project: project_name(record.cwd.as_deref()).unwrap_or_else(|| "Other".to_string()),
+                segments: self.session_segments(conn, &record)?,
+            });
+            for tool in &record.tool_usage {
+                match tools.iter_mut().find(|(name, _)| *name == tool.tool_name) {
+                    Some((_, uses)) => *uses += tool.uses,
+                    None => tools.push((tool.tool_name.clone(), tool.uses)),
                 }
-            );
-            self.current_session.issues.push(issue);
-        }
-    }
-
-    fn process_generic_activity(&mut self, event: &ClaudeEvent) {
-        // Process other types of activities
-        if let Some(response) = &event.assistant_response {
-            if response.len() > 50 {
-                let activity = format!("Analysis and response provided");
-                let accomplishment = Accomplishment {
-                    category: "Analysis".to_string(),
-                    description: activity,
-                    duration_ms: event.duration_ms,
-                    files_affected: Vec::new(),
-                };
-                self.current_session.accomplishments.push(accomplishment);
             }
+            sessions.push(record);
         }
+        tools.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        
+        std::fs::write(path, html::render(label, stats, &timeline, &tools, &sessions))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        println!("Wrote {} report to {}", label.to_lowercase(), path.display());
+        Ok(())
     }
 
-    fn categorize_tool(&self, tool_name: &str) -> String {
-        match tool_name {
-            "Edit" | "Write" | "MultiEdit" => "Code Development".to_string(),
-            "Read" | "Glob" | "LS" => "Code Analysis".to_string(),
-            "Bash" => "System Operations".to_string(),
-            "Grep" => "Code Search".to_string(),
-            "Task" => "AI Collaboration".to_string(),
-            "TodoWrite" => "Project Management".to_string(),
-            "WebFetch" => "Research".to_string(),
-            _ => "Other".to_string(),
-        }
-    }
-
-    fn save_session_to_db(&mut self) -> Result<()> {
+    fn show_stats(&self, range: DateRange, project: Option<&str>, today: NaiveDate) -> Result<()> {
         if self.test_mode {
-            let content = self.generate_diary_content();
-            let today = Local::now().format("%Y-%m-%d").to_string();
-            println!("=== DIARY ENTRY FOR {} ===", today);
-            println!("{}", content);
+            println!("Stats not available in test mode");
             return Ok(());
         }
         
-        let session_id = self.get_or_create_session()?;
         let conn = Connection::open(&self.db_path)?;
-        
-        // Update session end time
-        conn.execute(
-            "UPDATE sessions SET end_time = ?1, total_duration_ms = ?2 WHERE id = ?3",
-            params![
-                self.current_session.end_time.map(|t| t.to_rfc3339()),
-                self.current_session.total_duration_ms as i64,
-                session_id
-            ],
+        let mut tools_stmt = conn.prepare(
+            "SELECT tool_name, SUM(usage_count) FROM tool_usage WHERE session_id = ?1 GROUP BY tool_name"
+        )?;
+        let mut files_stmt = conn.prepare(
+            "SELECT file_path, SUM(edit_count) FROM files_modified WHERE session_id = ?1 GROUP BY file_path"
         )?;
+        let mut category_stmt = conn.prepare(&format!(
+            "SELECT category FROM accomplishments WHERE session_id = ?1 AND {} AND {}",
+            status::CONFIRMED_SQL,
+            status::TODOS_REPLACE_PROMPTS_SQL
+        ))?;
+        let mut issues_stmt = conn.prepare("SELECT tool_name FROM issues WHERE session_id = ?1")?;
         
-        // Save accomplishments
-        for accomplishment in &self.current_session.accomplishments {
-            let acc_id: i64 = conn.query_row(
-                "INSERT INTO accomplishments (session_id, category, description, duration_ms) 
-                 VALUES (?1, ?2, ?3, ?4) RETURNING id",
-                params![
-                    session_id,
-                    &accomplishment.category,
-                    &accomplishment.description,
-                    accomplishment.duration_ms.map(|d| d as i64)
-                ],
-                |row| row.get(0),
-            )?;
+        let mut stats = stats::UsageStats::new(range, project);
+        for StoredSession { id: session_id, day, active_ms: duration_ms, cwd } in self.sessions_started_in(&conn, &range)? {
+            if project.is_some_and(|project| project_name(cwd.as_deref()).as_deref() != Some(project)) {
+                continue;
+            }
+            stats.add_session(day, duration_ms);
             
-            // Save files affected by this accomplishment
-            for file_path in &accomplishment.files_affected {
-                conn.execute(
-                    "INSERT INTO accomplishment_files (accomplishment_id, file_path) VALUES (?1, ?2)",
-                    params![acc_id, file_path],
-                )?;
+            let tools = tools_stmt
+                .query_map([session_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            for (tool_name, calls) in tools {
+                stats.add_tool_calls(day, &tool_name, calls);
+            }
+            let files = files_stmt
+                .query_map([session_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            for (path, edits) in files {
+                stats.add_file(&path, edits);
+            }
+            for category in category_stmt.query_map([session_id], |row| row.get::<_, String>(0))? {
+                stats.add_accomplishment(&category?);
+            }
+            for tool_name in issues_stmt.query_map([session_id], |row| row.get::<_, Option<String>>(0))? {
+                stats.add_error(tool_name?.as_deref());
             }
         }
+        stats.finish(today);
         
-        // Save objectives
-        for objective in &self.current_session.objectives {
-            conn.execute(
-                "INSERT INTO objectives (session_id, objective) VALUES (?1, ?2)",
-                params![session_id, objective],
-            )?;
+        if self.format != OutputFormat::Markdown {
+            return Report::new("stats").records("stats", "stats", &[stats])?.print(self.format);
         }
         
-        // Save issues
-        for issue in &self.current_session.issues {
-            conn.execute(
-                "INSERT INTO issues (session_id, issue) VALUES (?1, ?2)",
-                params![session_id, issue],
-            )?;
+        print!("\n{}", stats::format(&stats));
+        Ok(())
+    }
+
+    /// Print a CSV timesheet of active time per day and project in `range`.
+    fn show_timesheet(&self, range: DateRange) -> Result<()> {
+        if self.test_mode {
+            println!("Timesheet not available in test mode");
+            return Ok(());
         }
         
-        // Save tool usage
-        for (tool_name, count) in &self.current_session.tool_usage {
-            conn.execute(
-                "INSERT INTO tool_usage (session_id, tool_name, usage_count) VALUES (?1, ?2, ?3)",
-                params![session_id, tool_name, *count as i64],
-            )?;
-        }
+        let conn = Connection::open(&self.db_path)?;
+        // Tool-derived accomplishments ("Read x") are too fine-grained to bill
+        let mut acc_stmt = conn.prepare(&format!(
+            "SELECT description FROM accomplishments 
+             WHERE session_id = ?1 AND {} AND {} AND source IN ('prompt', 'todo', 'response')
+             ORDER BY id",
+            status::CONFIRMED_SQL,
+            status::TODOS_REPLACE_PROMPTS_SQL
+        ))?;
         
-        // Save modified files
-        let mut unique_files: Vec<_> = self.current_session.files_modified.iter().collect();
-        unique_files.sort();
-        unique_files.dedup();
-        for file_path in unique_files {
-            conn.execute(
-                "INSERT INTO files_modified (session_id, file_path) VALUES (?1, ?2)",
-                params![session_id, file_path],
-            )?;
+        let mut days: Vec<timesheet::DayWork> = Vec::new();
+        for StoredSession { id: session_id, day, active_ms: duration_ms, cwd } in self.sessions_started_in(&conn, &range)? {
+            let project = project_name(cwd.as_deref()).unwrap_or_else(|| "Other".to_string());
+            let client = cwd.as_deref().and_then(|cwd| timesheet::client_for(cwd, &self.config.timesheet.clients));
+            let descriptions = acc_stmt
+                .query_map([session_id], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            timesheet::add_session(&mut days, day, project, client, duration_ms, descriptions);
         }
         
+        // Rows that round down to nothing are left out
+        let rows: Vec<timesheet::TimesheetRow> = days
+            .into_iter()
+            .filter_map(|work| work.into_row(&self.config.timesheet))
+            .collect();
         
-        if self.verbose {
-            eprintln!("Saved session {} to database: {:?}", session_id, self.db_path);
+        if self.format != OutputFormat::Markdown {
+            return Report::new("timesheet")
+                .field("since", range.since)?
+                .field("until", range.until)?
+                .records("rows", "timesheet_row", &rows)?
+                .print(self.format);
         }
         
+        print!("{}", timesheet::to_csv(&rows));
         Ok(())
     }
 
-    fn generate_diary_content(&self) -> String {
-        let mut content = String::new();
-        
-        let duration_mins = self.current_session.total_duration_ms / 60000;
-        let duration_display = if duration_mins > 0 {
-            format!("~{} minutes", duration_mins)
-        } else {
-            "< 1 minute".to_string()
-        };
-
-        // Group accomplishments by category
-        let mut categories: HashMap<String, Vec<&Accomplishment>> = HashMap::new();
-        for acc in &self.current_session.accomplishments {
-            categories.entry(acc.category.clone()).or_insert(Vec::new()).push(acc);
+    /// A session's active segments. Sessions recorded before event times
+    /// were stored are one block from their start.
+    fn session_segments(&self, conn: &Connection, record: &output::SessionRecord) -> Result<Vec<activity::Segment>> {
+        let mut segments = activity::segments(&self.load_session_events(conn, record.id)?, self.config.idle_gap());
+        if segments.is_empty() {
+            if let Some(start) = dates::local_time(&record.start_time) {
+                segments.push(activity::Segment { start, end: start + chrono::Duration::milliseconds(record.active_ms) });
+            }
         }
-
-        content.push_str(&format!("\n### ✅ **Accomplishments** _({})*\n\n", duration_display));
-
-        for (category, accomplishments) in categories {
-            content.push_str(&format!("#### **{}**\n", category));
-            for acc in accomplishments {
-                let duration_str = if let Some(duration) = acc.duration_ms {
-                    format!(" _({}ms)_", duration)
-                } else {
-                    String::new()
-                };
-                content.push_str(&format!("- **{}**{}\n", acc.description, duration_str));
-                
-                if !acc.files_affected.is_empty() {
-                    content.push_str("  - Files: ");
-                    content.push_str(&acc.files_affected.join(", "));
-                    content.push_str("\n");
+        Ok(segments)
+    }
+    
+    fn export_ics(&self, range: DateRange) -> Result<()> {
+        if self.test_mode {
+            println!("Export not available in test mode");
+            return Ok(());
+        }
+        
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare("SELECT id FROM sessions ORDER BY start_time")?;
+        let session_ids = stmt.query_map([], |row| row.get::<_, i64>(0))?.collect::<Result<Vec<_>, _>>()?;
+        let mut access_stmt = conn.prepare(
+            "SELECT file_path, accessed_at FROM file_access 
+             WHERE session_id = ?1 AND access_type IN ('created', 'edited', 'deleted') ORDER BY id"
+        )?;
+        
+        let mut events = Vec::new();
+        for session_id in session_ids {
+            let record = self.load_session_record(&conn, session_id, DateRange::default())?;
+            let segments = self.session_segments(&conn, &record)?;
+            if segments.is_empty() || !segments.iter().any(|segment| range.contains(segment.start.date_naive())) {
+                continue;
+            }
+            
+            let resolve = |path: &str| match &record.cwd {
+                Some(cwd) => Path::new(cwd).join(path).to_string_lossy().to_string(),
+                None => path.to_string(),
+            };
+            let mut descriptions: Vec<Vec<String>> = vec![Vec::new(); segments.len()];
+            for accomplishment in &record.accomplishments {
+                let index = accomplishment
+                    .created_at
+                    .as_deref()
+                    .and_then(dates::local_time)
+                    .map(|time| ics::segment_for(&segments, time))
+                    .unwrap_or(segments.len() - 1);
+                descriptions[index].push(format!("- {}: {}", accomplishment.category, accomplishment.description));
+            }
+            let mut attachments: Vec<Vec<String>> = vec![Vec::new(); segments.len()];
+            let accesses = access_stmt
+                .query_map([session_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            for (path, accessed_at) in accesses {
+                let index = dates::local_time(&accessed_at)
+                    .map(|time| ics::segment_for(&segments, time))
+                    .unwrap_or(segments.len() - 1);
+                standup::push_unique(&mut attachments[index], resolve(&path));
+            }
+            // Files known only from git or older sessions go with the last segment
+            for file in &record.files_modified {
+                let path = resolve(&file.path);
+                if !attachments.iter().any(|paths| paths.contains(&path)) {
+                    attachments[segments.len() - 1].push(path);
                 }
             }
-            content.push_str("\n");
-        }
-
-        if !self.current_session.objectives.is_empty() {
-            content.push_str("### 🎯 **Session Objectives**\n");
-            for obj in &self.current_session.objectives {
-                content.push_str(&format!("- {}\n", obj));
+            
+            let session_key = record.claude_session_id.clone().unwrap_or_else(|| format!("session-{}", session_id));
+            let project = project_name(record.cwd.as_deref()).unwrap_or_else(|| "Claude session".to_string());
+            for ((segment, description), files) in segments.iter().zip(descriptions).zip(attachments) {
+                if !range.contains(segment.start.date_naive()) {
+                    continue;
+                }
+                events.push(ics::CalendarEvent {
+                    uid: ics::uid(&session_key, segment),
+                    start: segment.start,
+                    // A lone event still gets a visible block
+                    end: segment.end.max(segment.start + chrono::Duration::minutes(1)),
+                    summary: project.clone(),
+                    description: description.join("\n"),
+                    attachments: files,
+                });
             }
-            content.push_str("\n");
         }
+        
+        print!("{}", ics::to_ics(&events));
+        Ok(())
+    }
 
-        if !self.current_session.issues.is_empty() {
-            content.push_str("### ⚠️ **Issues Encountered**\n");
-            for issue in &self.current_session.issues {
-                content.push_str(&format!("- {}\n", issue));
-            }
-            content.push_str("\n");
+    /// Write the diary for `day` into its daily note. Days without sessions
+    /// are left alone; returns the note's path when one was written.
+    fn write_vault_note(&self, conn: &Connection, day: NaiveDate, vault: Option<&str>) -> Result<Option<PathBuf>> {
+        let range = DateRange::day(day);
+        let session_ids = self.sessions_recorded_in(conn, &range)?;
+        if session_ids.is_empty() {
+            return Ok(None);
         }
-
-        if !self.current_session.tool_usage.is_empty() {
-            content.push_str("### 🛠 **Tools Used**\n");
-            for (tool, count) in &self.current_session.tool_usage {
-                content.push_str(&format!("- {}: {} times\n", tool, count));
+        
+        let sessions = session_ids
+            .into_iter()
+            .map(|session| self.load_session_record(conn, session.id, range))
+            .collect::<Result<Vec<_>>>()?;
+        let narrative: Option<String> = conn
+            .query_row("SELECT narrative FROM daily_summaries WHERE day = ?1", [day.to_string()], |row| row.get(0))
+            .ok();
+        let path = vault::note_path(&self.config.vault, vault, day)?;
+        let block = vault::format_day(day, narrative.as_deref(), &sessions, self.config.vault.heading_level);
+        vault::write_note(&path, &block)?;
+        Ok(Some(path))
+    }
+    
+    fn export_vault(&self, range: DateRange, vault: Option<&str>) -> Result<()> {
+        if self.test_mode {
+            println!("Export not available in test mode");
+            return Ok(());
+        }
+        
+        let conn = Connection::open(&self.db_path)?;
+        // Open ends of the range stop at the first and last recorded sessions
+        let (first, last): (Option<String>, Option<String>) =
+            conn.query_row("SELECT MIN(start_time), MAX(start_time) FROM sessions", [], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        let today = Local::now().date_naive();
+        let Some(mut day) = range.since.or_else(|| first.as_deref().and_then(dates::local_day)) else {
+            println!("No sessions to export");
+            return Ok(());
+        };
+        let until = range.until.unwrap_or_else(|| last.as_deref().and_then(dates::local_day).unwrap_or(today).max(today));
+        
+        let mut written = 0;
+        while day <= until {
+            if let Some(path) = self.write_vault_note(&conn, day, vault)? {
+                println!("Updated {}", path.display());
+                written += 1;
             }
-            content.push_str("\n");
+            day += chrono::Duration::days(1);
+        }
+        if written == 0 {
+            println!("No sessions in this period");
         }
+        Ok(())
+    }
 
-        if !self.current_session.files_modified.is_empty() {
-            content.push_str("### 📁 **Files Modified**\n");
-            let mut unique_files: Vec<_> = self.current_session.files_modified.iter().collect();
-            unique_files.sort();
-            unique_files.dedup();
-            for file in unique_files {
-                content.push_str(&format!("- {}\n", file));
+    fn show_tech_report(&self, range: DateRange) -> Result<()> {
+        if self.test_mode {
+            println!("Tech report not available in test mode");
+            return Ok(());
+        }
+        
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare(
+            "SELECT s.start_time, s.total_duration_ms, t.name, t.kind 
+             FROM session_technologies t JOIN sessions s ON s.id = t.session_id 
+             ORDER BY s.start_time"
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?.collect::<Result<Vec<_>, _>>()?;
+        
+        // Day boundaries are local, so filter after converting each start time
+        let mut totals: Vec<(String, String, usize, i64)> = Vec::new();
+        for (start_time, duration_ms, name, kind) in rows {
+            let day = DateTime::parse_from_rfc3339(&start_time)?.with_timezone(&Local).date_naive();
+            if !range.contains(day) {
+                continue;
             }
-            content.push_str("\n");
+            match totals.iter_mut().find(|(n, _, _, _)| *n == name) {
+                Some((_, _, sessions, total)) => {
+                    *sessions += 1;
+                    *total += duration_ms;
+                }
+                None => totals.push((name, kind, 1, duration_ms)),
+            }
+        }
+        totals.sort_by(|a, b| b.3.cmp(&a.3).then(b.2.cmp(&a.2)).then(a.0.cmp(&b.0)));
+        
+        if self.format != OutputFormat::Markdown {
+            let records: Vec<output::TechnologyRecord> = totals
+                .into_iter()
+                .map(|(name, kind, sessions, active_ms)| output::TechnologyRecord { name, kind, sessions, active_ms })
+                .collect();
+            return Report::new("tech")
+                .field("since", range.since)?
+                .field("until", range.until)?
+                .records("technologies", "technology", &records)?
+                .print(self.format);
+        }
+        
+        println!("\n=== TECH STACK ({}) ===\n", range.describe());
+        
+        if totals.is_empty() {
+            println!("No technologies recorded");
+            return Ok(());
         }
+        for (name, kind, sessions, total_ms) in totals {
+            println!(
+                "- **{}** _({})_: {} across {} session{}",
+                name,
+                kind,
+                format_duration(total_ms),
+                sessions,
+                if sessions == 1 { "" } else { "s" }
+            );
+        }
+        Ok(())
+    }
 
-        content.push_str("---\n");
+    /// Session ids with an accomplishment or issue recorded on a day in `range`.
+    fn sessions_active_in(&self, conn: &Connection, range: DateRange) -> Result<Vec<i64>> {
+        let mut stmt = conn.prepare(
+            "SELECT session_id, created_at FROM accomplishments 
+             UNION ALL SELECT session_id, created_at FROM issues"
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?))
+        })?;
         
-        content
+        let mut active = Vec::new();
+        for row in rows {
+            let (session_id, created_at) = row?;
+            let in_range = created_at.as_deref().and_then(dates::local_day).is_some_and(|day| range.contains(day));
+            if in_range && !active.contains(&session_id) {
+                active.push(session_id);
+            }
+        }
+        Ok(active)
     }
     
-    
-    fn show_recent_entries(&self, limit: usize) -> Result<()> {
+    /// Print sessions, newest first. With a bounded range, a session is shown
+    /// if it started in the range or recorded accomplishments or issues in it,
+    /// and only the accomplishments and issues from those days are listed.
+    fn show_entries(&self, range: DateRange, limit: Option<usize>) -> Result<()> {
         if self.test_mode {
             println!("Recent entries not available in test mode");
             return Ok(());
         }
         
         let conn = Connection::open(&self.db_path)?;
-        
+        let active_in_range = if range.is_unbounded() {
+            Vec::new()
+        } else {
+            self.sessions_active_in(&conn, range)?
+        };
         let mut stmt = conn.prepare(
-            "SELECT id, start_time, end_time, total_duration_ms FROM sessions 
-             ORDER BY start_time DESC LIMIT ?1"
+            "SELECT id, start_time, end_time, total_duration_ms, narrative, active_segments FROM sessions 
+             ORDER BY start_time DESC"
         )?;
         
-        let session_rows = stmt.query_map([limit], |row| {
+        let session_rows = stmt.query_map([], |row| {
             Ok((
                 row.get::<_, i64>(0)?,
                 row.get::<_, String>(1)?,
                 row.get::<_, Option<String>>(2)?,
                 row.get::<_, i64>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, i64>(5)?,
             ))
         })?;
         
-        println!("\n=== RECENT DIARY ENTRIES ===");
+        let markdown = self.format == OutputFormat::Markdown;
+        if markdown {
+            if range.is_unbounded() {
+                println!("\n=== RECENT DIARY ENTRIES ===");
+            } else {
+                println!("\n=== DIARY ENTRIES ({}) ===", range.describe());
+            }
+        }
         
+        let mut records = Vec::new();
+        let mut shown = 0;
+        let mut current_day = None;
         for session_result in session_rows {
-            let (session_id, start_time, _end_time, total_duration_ms) = session_result?;
+            let (session_id, start_time, _end_time, total_duration_ms, session_narrative, active_segments) = session_result?;
             
             let start_dt = DateTime::parse_from_rfc3339(&start_time)?
                 .with_timezone(&Local);
+            if !range.contains(start_dt.date_naive()) && !active_in_range.contains(&session_id) {
+                continue;
+            }
+            if limit.is_some_and(|limit| shown >= limit) {
+                break;
+            }
+            shown += 1;
+            if !markdown {
+                records.push(self.load_session_record(&conn, session_id, range)?);
+                continue;
+            }
             
-            let duration_mins = total_duration_ms / 60000;
-            let duration_display = if duration_mins > 0 {
-                format!("~{} minutes", duration_mins)
-            } else {
-                "< 1 minute".to_string()
-            };
+            // Open each day with its roll-up
+            let day = start_dt.date_naive();
+            if current_day != Some(day) {
+                current_day = Some(day);
+                let day_narrative: Option<String> = conn.query_row(
+                    "SELECT narrative FROM daily_summaries WHERE day = ?1",
+                    [day.to_string()],
+                    |row| row.get(0),
+                ).ok();
+                if let Some(day_narrative) = day_narrative {
+                    println!("\n# {}\n\n{}", day.format("%A, %Y-%m-%d"), day_narrative);
+                }
+            }
+            
+            let duration_display = format_active_time(total_duration_ms, active_segments);
             
             println!("\n## Session {} - {}", 
                 start_dt.format("%Y-%m-%d %H:%M:%S"),
                 duration_display
             );
+            if let Some(session_narrative) = session_narrative {
+                println!("\n{}", session_narrative);
+            }
             
             // Get accomplishments
-            let mut acc_stmt = conn.prepare(
-                "SELECT category, description, duration_ms FROM accomplishments 
-                 WHERE session_id = ?1 ORDER BY id"
-            )?;
+            let mut acc_stmt = conn.prepare(&format!(
+                "SELECT category, description, duration_ms, status, created_at FROM accomplishments 
+                 WHERE session_id = ?1 AND {} ORDER BY id",
+                status::TODOS_REPLACE_PROMPTS_SQL
+            ))?;
             
             let accomplishments = acc_stmt.query_map([session_id], |row| {
                 Ok((
                     row.get::<_, String>(0)?,
                     row.get::<_, String>(1)?,
                     row.get::<_, Option<i64>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
                 ))
             })?;
             
-            let mut categories: HashMap<String, Vec<(String, Option<i64>)>> = HashMap::new();
+            // Rows from before statuses existed count as confirmed
+            let mut categories: HashMap<String, Vec<(String, Option<i64>, String)>> = HashMap::new();
+            let mut hidden = 0;
             for acc_result in accomplishments {
-                let (category, description, duration_ms) = acc_result?;
-                categories.entry(category).or_insert(Vec::new()).push((description, duration_ms));
+                let (category, description, duration_ms, acc_status, created_at) = acc_result?;
+                if !recorded_in(range, created_at.as_deref()) {
+                    continue;
+                }
+                let acc_status = acc_status.unwrap_or_else(|| status::CONFIRMED.to_string());
+                if acc_status != status::CONFIRMED && !self.include_unconfirmed {
+                    hidden += 1;
+                    continue;
+                }
+                categories.entry(category).or_default().push((description, duration_ms, acc_status));
             }
             
             if !categories.is_empty() {
                 println!("\n### ✅ **Accomplishments**");
                 for (category, accs) in categories {
                     println!("\n#### **{}**", category);
-                    for (desc, duration_ms) in accs {
+                    for (desc, duration_ms, acc_status) in accs {
                         let duration_str = if let Some(duration) = duration_ms {
                             format!(" _({}ms)_", duration)
                         } else {
                             String::new()
                         };
-                        println!("- **{}**{}", desc, duration_str);
+                        println!("- **{}**{}{}", desc, duration_str, status_label(&acc_status));
                     }
                 }
             }
+            if let Some(hint) = unconfirmed_hint(hidden) {
+                println!("\n{}", hint);
+            }
             
             // Get objectives
             let mut obj_stmt = conn.prepare(
@@ -852,7 +3208,7 @@ impl DiaryManager {
             )?;
             
             let objectives = obj_stmt.query_map([session_id], |row| {
-                Ok(row.get::<_, String>(0)?)
+                row.get::<_, String>(0)
             })?;
             
             let obj_list: Result<Vec<String>, _> = objectives.collect();
@@ -865,21 +3221,244 @@ impl DiaryManager {
                 }
             }
             
+            // Get issues, including failed tool calls
+            let mut issue_stmt = conn.prepare(
+                "SELECT issue, created_at FROM issues WHERE session_id = ?1 ORDER BY id"
+            )?;
+            let issue_list = issue_stmt
+                .query_map([session_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .filter(|(_, created_at)| recorded_in(range, created_at.as_deref()))
+                .map(|(issue, _)| issue)
+                .collect::<Vec<_>>();
+            if !issue_list.is_empty() {
+                println!("\n### ⚠️ **Issues Encountered**");
+                for issue in issue_list {
+                    println!("- {}", issue);
+                }
+            }
+            
+            // Get ticket and issue references
+            let refs = self.load_references(&conn, session_id)?;
+            if !refs.is_empty() {
+                println!("\n### 🎫 **References**");
+                for reference in refs {
+                    println!("- {} _({}, from {})_", reference.key, reference.kind, reference.source);
+                }
+            }
+            
+            // Get the session's tech stack
+            let technologies = self.load_technologies(&conn, session_id)?;
+            if !technologies.is_empty() {
+                print!("\n{}", tech_stack::format_section(&technologies));
+            }
+            
+            // Get slash commands
+            let commands = self.load_slash_commands(&conn, session_id)?;
+            if !commands.is_empty() {
+                print!("\n{}", prompt_clean::format_section(&commands));
+            }
+            
+            // Get todo items
+            let todo_items = self.load_todos(&conn, session_id)?;
+            if !todo_items.is_empty() {
+                print!("\n{}", todos::format_section(&todo_items));
+            }
+            
+            // Get test runs
+            let runs = self.load_test_runs(&conn, session_id)?;
+            if !runs.is_empty() {
+                print!("\n{}", test_runs::format_section(&runs));
+            }
+            
+            // Get modified files with their churn
+            let files = self.load_files_modified(&conn, session_id)?;
+            if !files.is_empty() {
+                print!("\n{}", churn::format_section(&files));
+            }
+            
+            // Get files that were only read or searched
+            let explored = file_access::explored(&self.load_file_accesses(&conn, session_id)?);
+            if !explored.is_empty() {
+                print!("\n{}", file_access::format_section(&explored));
+            }
+            
+            // Get git activity
+            let snapshots = self.load_git_snapshots(&conn, session_id)?;
+            let commits = self.load_git_commits(&conn, session_id)?;
+            if !snapshots.is_empty() || !commits.is_empty() {
+                print!("\n{}", git::format_section(snapshots.last(), &commits));
+            }
+            
             println!("\n---");
         }
         
+        if !markdown {
+            return Report::new("sessions")
+                .field("since", range.since)?
+                .field("until", range.until)?
+                .records("sessions", "session", &records)?
+                .print(self.format);
+        }
+        if shown == 0 {
+            println!("\nNo sessions in this period");
+        }
+        
         Ok(())
     }
 }
 
+/// Whether a row recorded at `created_at` falls in `range`. Rows without a
+/// timestamp, from older versions, always do.
+fn recorded_in(range: DateRange, created_at: Option<&str>) -> bool {
+    range.is_unbounded() || created_at.and_then(dates::local_day).is_none_or(|day| range.contains(day))
+}
+
+/// The name of the directory a session worked in.
+fn project_name(cwd: Option<&str>) -> Option<String> {
+    let cwd = cwd?;
+    Path::new(cwd)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .or_else(|| Some(cwd.to_string()))
+}
+
+/// What a session was about: its first ticket, or failing that its first objective.
+fn session_topic(refs: &[Reference], first_objective: Option<&String>) -> Option<String> {
+    refs.iter()
+        .find(|r| r.kind == "ticket" || r.kind == "issue")
+        .or_else(|| refs.first())
+        .map(|r| r.key.clone())
+        .or_else(|| {
+            // "Fix the login bug" reads as "Worked on fix the login bug", but keep "API ..." as is
+            let objective = first_objective?.trim();
+            let mut chars = objective.chars();
+            let first = chars.next()?;
+            match chars.next() {
+                Some(second) if second.is_lowercase() => {
+                    Some(first.to_lowercase().chain(objective.chars().skip(1)).collect())
+                }
+                _ => Some(objective.to_string()),
+            }
+        })
+}
+
+/// Suffix marking accomplishments that are not confirmed yet.
+fn status_label(acc_status: &str) -> String {
+    if acc_status == status::CONFIRMED {
+        String::new()
+    } else {
+        format!(" _[{}]_", acc_status.replace('_', " "))
+    }
+}
+
+fn unconfirmed_hint(hidden: usize) -> Option<String> {
+    if hidden == 0 {
+        return None;
+    }
+    Some(format!(
+        "_{} unconfirmed accomplishment{} hidden, use --include-unconfirmed to show_",
+        hidden,
+        if hidden == 1 { "" } else { "s" }
+    ))
+}
+
+fn format_duration(duration_ms: i64) -> String {
+    let duration_mins = duration_ms / 60000;
+    if duration_mins >= 60 {
+        format!("~{}h {:02}m", duration_mins / 60, duration_mins % 60)
+    } else if duration_mins > 0 {
+        format!("~{} minutes", duration_mins)
+    } else {
+        "< 1 minute".to_string()
+    }
+}
+
+/// A session's active time, noting how many segments it was split into by idle gaps.
+fn format_active_time(duration_ms: i64, segments: i64) -> String {
+    if segments > 1 {
+        format!("{} active in {} segments", format_duration(duration_ms), segments)
+    } else {
+        format_duration(duration_ms)
+    }
+}
+
+/// Add a column to an existing table, for databases created by older versions.
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|name| name.ok())
+        .any(|name| name == column);
+    
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
     let mut diary_manager = DiaryManager::new(args.diary_dir, args.verbose, args.test)?;
+    diary_manager.include_unconfirmed = args.include_unconfirmed;
+    diary_manager.format = args.format;
+    
+    // Only the calendar export is written as iCalendar
+    if args.format == OutputFormat::Ics && !matches!(args.command, Some(Command::Export { target: None, .. })) {
+        bail!("--format ics only applies to export");
+    }
+
+    if let Some(command) = args.command {
+        return match command {
+            Command::Ticket { key } => diary_manager.show_ticket(&key),
+            Command::Tools => diary_manager.show_tool_failures(),
+            Command::Tech { since, until } => diary_manager.show_tech_report(DateRange { since, until }),
+            Command::Standup { date, plain } => {
+                diary_manager.show_standup(date.unwrap_or_else(|| Local::now().date_naive()), plain)
+            }
+            Command::Report { week: _, month, date, html } => {
+                let day = date.unwrap_or_else(|| Local::now().date_naive());
+                diary_manager.show_period_report(day, month, html.as_deref())
+            }
+            Command::Show { period, date, since, until, limit } => {
+                let today = Local::now().date_naive();
+                let range = match date {
+                    Some(date) => DateRange::day(date),
+                    None => dates::resolve(period.as_deref(), since, until, DateRange::day(today), today)?,
+                };
+                diary_manager.show_entries(range, limit)
+            }
+            Command::Export { target: None, period, since, until } => {
+                if args.format != OutputFormat::Ics {
+                    bail!("Choose what to export: --format ics for calendar events, or `export vault`");
+                }
+                let today = Local::now().date_naive();
+                let range = dates::resolve(period.as_deref(), since, until, DateRange::default(), today)?;
+                diary_manager.export_ics(range)
+            }
+            Command::Export { target: Some(ExportTarget::Vault { period, since, until, vault }), .. } => {
+                let today = Local::now().date_naive();
+                let range = dates::resolve(period.as_deref(), since, until, DateRange::day(today), today)?;
+                diary_manager.export_vault(range, vault.as_deref())
+            }
+            Command::Stats { period, since, until, project } => {
+                let today = Local::now().date_naive();
+                let range = dates::resolve(period.as_deref(), since, until, DateRange::default(), today)?;
+                diary_manager.show_stats(range, project.as_deref(), today)
+            }
+            Command::Timesheet { period, since, until } => {
+                let today = Local::now().date_naive();
+                let range = dates::resolve(period.as_deref(), since, until, dates::week_of(today), today)?;
+                diary_manager.show_timesheet(range)
+            }
+        };
+    }
 
     // If user wants to show recent entries, do that and exit
     if args.show_recent {
-        return diary_manager.show_recent_entries(args.limit);
+        return diary_manager.show_entries(DateRange::default(), Some(args.limit));
     }
 
     let stdin = io::stdin();
@@ -894,7 +3473,7 @@ async fn main() -> Result<()> {
 
         match serde_json::from_str::<ClaudeEvent>(&line) {
             Ok(event) => {
-                if let Err(e) = diary_manager.process_event(event) {
+                if let Err(e) = diary_manager.process_event(event.normalize()) {
                     eprintln!("Error processing event: {}", e);
                 }
             }
@@ -906,13 +3485,8 @@ async fn main() -> Result<()> {
                 let simple_event = ClaudeEvent {
                     event_type: "message".to_string(),
                     timestamp: Some(Local::now().to_rfc3339()),
-                    context: None,
-                    session_id: None,
                     user_prompt: Some(line),
-                    assistant_response: None,
-                    tool_calls: None,
-                    duration_ms: None,
-                    error: None,
+                    ..Default::default()
                 };
                 if let Err(e) = diary_manager.process_event(simple_event) {
                     eprintln!("Error processing simple event: {}", e);
@@ -921,8 +3495,9 @@ async fn main() -> Result<()> {
         }
     }
 
-    // Handle session end if not explicitly received  
-    diary_manager.current_session.end_time = Some(Local::now());
+    // Handle session end if not explicitly received, at the last event's time
+    let last_event_at = diary_manager.current_session.events.last().map(|event| event.occurred_at);
+    diary_manager.current_session.end_time = Some(last_event_at.unwrap_or_else(Local::now));
     diary_manager.save_session_to_db()?;
 
     Ok(())
@@ -0,0 +1,186 @@
+//! Short prose summaries of a session and of a day, composed from fixed
+//! templates so the same activity always reads the same way.
+
+use crate::text::ellipsize;
+
+/// What a session did, reduced to the facts the narrative mentions.
+#[derive(Debug, Clone, Default)]
+pub struct SessionFacts {
+    /// Name of the directory the session worked in
+    pub project: Option<String>,
+    /// A ticket key or the first objective
+    pub topic: Option<String>,
+    pub files_edited: usize,
+    pub files_created: usize,
+    pub files_deleted: usize,
+    pub files_explored: usize,
+    pub test_runs: usize,
+    /// Whether the last test run passed, if there was one
+    pub final_run_green: Option<bool>,
+    /// Failing tests in the last run
+    pub final_run_failed: u32,
+    pub commits: usize,
+    pub unresolved_errors: usize,
+    pub duration_ms: i64,
+}
+
+fn number(n: usize) -> String {
+    const WORDS: &[&str] = &["no", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten"];
+    WORDS.get(n).map(|w| w.to_string()).unwrap_or_else(|| n.to_string())
+}
+
+fn plural(n: usize, singular: &str, plural: &str) -> String {
+    format!("{} {}", number(n), if n == 1 { singular } else { plural })
+}
+
+fn times(n: usize) -> String {
+    match n {
+        1 => "once".to_string(),
+        2 => "twice".to_string(),
+        _ => format!("{} times", n),
+    }
+}
+
+/// The file changes clause, e.g. "edited 4 files and created 1".
+fn changes_clause(edited: usize, created: usize, deleted: usize) -> Option<String> {
+    let mut parts = Vec::new();
+    let mut noun_used = false;
+    for (count, verb) in [(edited, "edited"), (created, "created"), (deleted, "deleted")] {
+        if count == 0 {
+            continue;
+        }
+        // Only the first part names the noun: "edited 4 files and created 1"
+        if noun_used {
+            parts.push(format!("{} {}", verb, number(count)));
+        } else {
+            parts.push(format!("{} {}", verb, plural(count, "file", "files")));
+            noun_used = true;
+        }
+    }
+    match parts.len() {
+        0 => None,
+        1 => parts.pop(),
+        _ => {
+            let last = parts.pop().unwrap_or_default();
+            Some(format!("{} and {}", parts.join(", "), last))
+        }
+    }
+}
+
+fn tests_clause(runs: usize, final_green: Option<bool>, final_failed: u32) -> Option<String> {
+    if runs == 0 {
+        return None;
+    }
+    let outcome = match (final_green, runs) {
+        (Some(true), 1) => "and it passed".to_string(),
+        (Some(true), _) => "the final run passed".to_string(),
+        (_, 1) => format!("and it failed ({} failing)", final_failed),
+        _ => format!("the final run failed ({} failing)", final_failed),
+    };
+    let separator = if runs == 1 { " " } else { ", " };
+    Some(format!("ran tests {}{}{}", times(runs), separator, outcome))
+}
+
+fn errors_clause(unresolved: usize) -> Option<String> {
+    (unresolved > 0).then(|| plural(unresolved, "unresolved error", "unresolved errors"))
+}
+
+/// One paragraph describing a session, e.g. "Worked in api on PROJ-12;
+/// edited 4 files, ran tests twice, the final run passed; one unresolved error."
+pub fn session_narrative(facts: &SessionFacts) -> String {
+    let topic = facts.topic.as_deref().map(|t| ellipsize(t, 60));
+    let head = match (&facts.project, &topic) {
+        (Some(project), Some(topic)) => format!("Worked in {} on {}", project, topic),
+        (Some(project), None) => format!("Worked in {}", project),
+        (None, Some(topic)) => format!("Worked on {}", topic),
+        (None, None) => "Worked with Claude".to_string(),
+    };
+
+    let mut activity: Vec<String> = Vec::new();
+    activity.extend(changes_clause(facts.files_edited, facts.files_created, facts.files_deleted));
+    activity.extend(tests_clause(facts.test_runs, facts.final_run_green, facts.final_run_failed));
+    if facts.commits > 0 {
+        activity.push(format!("made {}", plural(facts.commits, "commit", "commits")));
+    }
+    if activity.is_empty() && facts.files_explored > 0 {
+        activity.push(format!("explored {} without changing any", plural(facts.files_explored, "file", "files")));
+    }
+
+    let mut sentence = head;
+    if !activity.is_empty() {
+        sentence.push_str("; ");
+        sentence.push_str(&activity.join(", "));
+    }
+    if let Some(errors) = errors_clause(facts.unresolved_errors) {
+        sentence.push_str("; ");
+        sentence.push_str(&errors);
+    }
+    sentence.push('.');
+    sentence
+}
+
+/// A roll-up of every session in a day. `duration` is already formatted.
+pub fn day_narrative(sessions: &[SessionFacts], duration: &str) -> String {
+    if sessions.is_empty() {
+        return "No sessions.".to_string();
+    }
+
+    let mut projects: Vec<&str> = Vec::new();
+    for project in sessions.iter().filter_map(|s| s.project.as_deref()) {
+        if !projects.contains(&project) {
+            projects.push(project);
+        }
+    }
+    let place = match projects.len() {
+        0 => String::new(),
+        1 => format!(" in {}", projects[0]),
+        2 => format!(" in {} and {}", projects[0], projects[1]),
+        n => format!(" in {}, {} and {} other projects", projects[0], projects[1], n - 2),
+    };
+
+    let sum = |f: fn(&SessionFacts) -> usize| sessions.iter().map(f).sum::<usize>();
+    let mut activity: Vec<String> = Vec::new();
+    activity.extend(changes_clause(
+        sum(|s| s.files_edited),
+        sum(|s| s.files_created),
+        sum(|s| s.files_deleted),
+    ));
+    let runs = sum(|s| s.test_runs);
+    if runs > 0 {
+        let last = sessions.iter().rev().find(|s| s.test_runs > 0);
+        let ending = match last.and_then(|s| s.final_run_green) {
+            Some(true) => "ending green",
+            _ => "ending with failures",
+        };
+        activity.push(format!("ran tests {}, {}", times(runs), ending));
+    }
+    let commits = sum(|s| s.commits);
+    if commits > 0 {
+        activity.push(format!("made {}", plural(commits, "commit", "commits")));
+    }
+
+    let mut sentence = format!(
+        "{}{} ({})",
+        capitalize(&plural(sessions.len(), "session", "sessions")),
+        place,
+        duration
+    );
+    if !activity.is_empty() {
+        sentence.push_str(": ");
+        sentence.push_str(&activity.join(", "));
+    }
+    if let Some(errors) = errors_clause(sum(|s| s.unresolved_errors)) {
+        sentence.push_str("; ");
+        sentence.push_str(&errors);
+    }
+    sentence.push('.');
+    sentence
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
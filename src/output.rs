@@ -0,0 +1,173 @@
+//! Machine-readable output for the read commands. Every JSON document and
+//! every JSON Lines record carries `schema_version` and `kind`; the schema
+//! is documented in the README and the version is bumped on any change that
+//! removes or renames a field.
+
+use anyhow::Result;
+use chrono::Local;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum OutputFormat {
+    /// Human-readable Markdown
+    #[default]
+    Markdown,
+    /// One JSON document
+    Json,
+    /// One JSON object per line, one line per record
+    Jsonl,
+    /// iCalendar events, for `export` only
+    Ics,
+}
+
+/// A diary session with everything recorded in it.
+#[derive(Serialize, Debug)]
+pub struct SessionRecord {
+    pub id: i64,
+    pub claude_session_id: Option<String>,
+    pub start_time: String,
+    pub end_time: Option<String>,
+    pub active_ms: i64,
+    pub active_segments: i64,
+    pub cwd: Option<String>,
+    pub narrative: Option<String>,
+    pub objectives: Vec<String>,
+    pub accomplishments: Vec<AccomplishmentRecord>,
+    pub issues: Vec<IssueRecord>,
+    pub tool_usage: Vec<ToolUsageRecord>,
+    pub files_modified: Vec<FileRecord>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AccomplishmentRecord {
+    pub category: String,
+    pub description: String,
+    pub status: String,
+    pub source: Option<String>,
+    pub duration_ms: Option<i64>,
+    pub created_at: Option<String>,
+    pub files: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct IssueRecord {
+    pub issue: String,
+    pub tool_name: Option<String>,
+    pub file_path: Option<String>,
+    pub created_at: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ToolUsageRecord {
+    pub tool_name: String,
+    pub category: String,
+    pub uses: i64,
+    pub successes: i64,
+    pub failures: i64,
+    pub mcp_server: Option<String>,
+    pub mcp_tool: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct FileRecord {
+    pub path: String,
+    pub lines_added: i64,
+    pub lines_removed: i64,
+    pub edits: i64,
+}
+
+/// One tool's totals in the `tools` report.
+#[derive(Serialize, Debug)]
+pub struct ToolRecord {
+    pub tool_name: String,
+    pub uses: u32,
+    pub failures: u32,
+    pub failure_rate: f64,
+    pub recent_errors: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct McpServerRecord {
+    pub server: String,
+    pub calls: u32,
+    pub failures: u32,
+    pub tools: Vec<McpToolRecord>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct McpToolRecord {
+    pub tool: String,
+    pub calls: u32,
+}
+
+/// One technology's totals in the `tech` report.
+#[derive(Serialize, Debug)]
+pub struct TechnologyRecord {
+    pub name: String,
+    pub kind: String,
+    pub sessions: usize,
+    pub active_ms: i64,
+}
+
+/// A report being assembled for JSON output: top-level fields plus one or
+/// more lists of records.
+pub struct Report {
+    kind: &'static str,
+    fields: Map<String, Value>,
+    lists: Vec<(&'static str, &'static str, Vec<Value>)>,
+}
+
+impl Report {
+    pub fn new(kind: &'static str) -> Self {
+        Self { kind, fields: Map::new(), lists: Vec::new() }
+    }
+
+    /// A top-level field of the JSON document. Not included in JSON Lines.
+    pub fn field(mut self, name: &str, value: impl Serialize) -> Result<Self> {
+        self.fields.insert(name.to_string(), serde_json::to_value(value)?);
+        Ok(self)
+    }
+
+    /// A list stored under `field` in the JSON document; in JSON Lines each
+    /// item is its own line with `kind` set to `record_kind`.
+    pub fn records<T: Serialize>(mut self, field: &'static str, record_kind: &'static str, items: &[T]) -> Result<Self> {
+        let values = items.iter().map(serde_json::to_value).collect::<Result<Vec<_>, _>>()?;
+        self.lists.push((field, record_kind, values));
+        Ok(self)
+    }
+
+    pub fn print(self, format: OutputFormat) -> Result<()> {
+        match format {
+            // Calendars are written by `export`, never as a report
+            OutputFormat::Markdown | OutputFormat::Ics => {}
+            OutputFormat::Json => {
+                let mut document = Map::new();
+                document.insert("schema_version".to_string(), SCHEMA_VERSION.into());
+                document.insert("kind".to_string(), self.kind.into());
+                document.insert("generated_at".to_string(), Local::now().to_rfc3339().into());
+                document.extend(self.fields);
+                for (field, _, values) in self.lists {
+                    document.insert(field.to_string(), Value::Array(values));
+                }
+                println!("{}", serde_json::to_string_pretty(&document)?);
+            }
+            OutputFormat::Jsonl => {
+                for (_, record_kind, values) in self.lists {
+                    for value in values {
+                        let mut line = Map::new();
+                        line.insert("schema_version".to_string(), SCHEMA_VERSION.into());
+                        line.insert("kind".to_string(), record_kind.into());
+                        if let Value::Object(fields) = value {
+                            line.extend(fields);
+                        }
+                        println!("{}", serde_json::to_string(&line)?);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
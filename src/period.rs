@@ -0,0 +1,175 @@
+//! Weekly and monthly roll-ups: where the time went and what came of it,
+//! compared with the period before.
+
+use crate::dates::DateRange;
+use crate::format_duration;
+use serde::Serialize;
+
+/// How many modified files the report lists.
+pub const TOP_FILES: usize = 10;
+
+/// Totals for one period.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct PeriodStats {
+    pub since: Option<chrono::NaiveDate>,
+    pub until: Option<chrono::NaiveDate>,
+    pub active_ms: i64,
+    pub sessions: usize,
+    pub commits: usize,
+    pub test_runs: usize,
+    pub green_test_runs: usize,
+    /// Active time per accomplishment category, most first
+    pub categories: Vec<NamedTime>,
+    /// Active time per project, most first
+    pub projects: Vec<NamedTime>,
+    pub top_files: Vec<FileActivity>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct NamedTime {
+    pub name: String,
+    pub active_ms: i64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FileActivity {
+    pub path: String,
+    pub edits: i64,
+    pub lines_changed: i64,
+}
+
+impl PeriodStats {
+    pub fn new(range: DateRange) -> Self {
+        Self { since: range.since, until: range.until, ..Default::default() }
+    }
+
+    /// Add a session's time to a project and split it across the categories of
+    /// its accomplishments, in proportion to how many each has. A session with
+    /// none counts as "Uncategorized".
+    pub fn add_session(&mut self, project: &str, active_ms: i64, categories: &[String]) {
+        self.sessions += 1;
+        self.active_ms += active_ms;
+        add_time(&mut self.projects, project, active_ms);
+
+        if categories.is_empty() {
+            add_time(&mut self.categories, "Uncategorized", active_ms);
+            return;
+        }
+        let share = active_ms / categories.len() as i64;
+        for category in categories {
+            add_time(&mut self.categories, category, share);
+        }
+    }
+
+    pub fn add_file(&mut self, path: &str, edits: i64, lines_changed: i64) {
+        match self.top_files.iter_mut().find(|file| file.path == path) {
+            Some(file) => {
+                file.edits += edits;
+                file.lines_changed += lines_changed;
+            }
+            None => self.top_files.push(FileActivity { path: path.to_string(), edits, lines_changed }),
+        }
+    }
+
+    /// Sort the breakdowns, largest first, and keep the busiest files.
+    pub fn finish(&mut self) {
+        for list in [&mut self.categories, &mut self.projects] {
+            list.sort_by(|a, b| b.active_ms.cmp(&a.active_ms).then_with(|| a.name.cmp(&b.name)));
+        }
+        self.top_files
+            .sort_by(|a, b| b.edits.cmp(&a.edits).then(b.lines_changed.cmp(&a.lines_changed)).then_with(|| a.path.cmp(&b.path)));
+        self.top_files.truncate(TOP_FILES);
+    }
+}
+
+fn add_time(list: &mut Vec<NamedTime>, name: &str, active_ms: i64) {
+    match list.iter_mut().find(|entry| entry.name == name) {
+        Some(entry) => entry.active_ms += active_ms,
+        None => list.push(NamedTime { name: name.to_string(), active_ms }),
+    }
+}
+
+/// "+3 (+50%)", "-1h 05m", "no change".
+fn change(current: i64, previous: i64, as_duration: bool) -> String {
+    let delta = current - previous;
+    if delta == 0 {
+        return "no change".to_string();
+    }
+    let sign = if delta > 0 { "+" } else { "-" };
+    let amount = if as_duration {
+        format_duration(delta.abs()).trim_start_matches('~').to_string()
+    } else {
+        delta.abs().to_string()
+    };
+    if previous == 0 {
+        format!("{}{}", sign, amount)
+    } else {
+        format!("{}{} ({}{:.0}%)", sign, amount, sign, (delta.abs() as f64 / previous as f64) * 100.0)
+    }
+}
+
+fn previous_time(list: &[NamedTime], name: &str) -> i64 {
+    list.iter().find(|entry| entry.name == name).map(|entry| entry.active_ms).unwrap_or(0)
+}
+
+fn push_breakdown(content: &mut String, title: &str, current: &[NamedTime], previous: &[NamedTime], total_ms: i64) {
+    content.push_str(&format!("### {}\n", title));
+    if current.is_empty() {
+        content.push_str("- Nothing recorded\n\n");
+        return;
+    }
+    for entry in current {
+        let percent = if total_ms > 0 { entry.active_ms * 100 / total_ms } else { 0 };
+        content.push_str(&format!(
+            "- **{}**: {} ({}%), {} vs previous\n",
+            entry.name,
+            format_duration(entry.active_ms),
+            percent,
+            change(entry.active_ms, previous_time(previous, &entry.name), true)
+        ));
+    }
+    content.push('\n');
+}
+
+/// Render the report for `current`, with changes against `previous`.
+pub fn format_report(label: &str, current: &PeriodStats, previous: &PeriodStats) -> String {
+    let range = DateRange { since: current.since, until: current.until };
+    let previous_range = DateRange { since: previous.since, until: previous.until };
+    let mut content = format!("## {} report: {}\n\n", label, range.describe());
+
+    content.push_str(&format!("_Compared with {}_\n\n", previous_range.describe()));
+    content.push_str("### 📊 **Totals**\n");
+    let rows = [
+        ("Active time", current.active_ms, previous.active_ms, true),
+        ("Sessions", current.sessions as i64, previous.sessions as i64, false),
+        ("Commits", current.commits as i64, previous.commits as i64, false),
+        ("Test runs", current.test_runs as i64, previous.test_runs as i64, false),
+    ];
+    for (name, now, before, as_duration) in rows {
+        let value = if as_duration { format_duration(now) } else { now.to_string() };
+        content.push_str(&format!("- **{}**: {}, {} vs previous\n", name, value, change(now, before, as_duration)));
+    }
+    if current.test_runs > 0 {
+        content.push_str(&format!("- **Green test runs**: {} of {}\n", current.green_test_runs, current.test_runs));
+    }
+    content.push('\n');
+
+    push_breakdown(&mut content, "🗂 **Time by Category**", &current.categories, &previous.categories, current.active_ms);
+    push_breakdown(&mut content, "📁 **Time by Project**", &current.projects, &previous.projects, current.active_ms);
+
+    content.push_str("### 📝 **Most Edited Files**\n");
+    if current.top_files.is_empty() {
+        content.push_str("- Nothing recorded\n");
+    }
+    for file in &current.top_files {
+        content.push_str(&format!(
+            "- {}: {} edit{}, {} lines changed\n",
+            file.path,
+            file.edits,
+            if file.edits == 1 { "" } else { "s" },
+            file.lines_changed
+        ));
+    }
+    content.push('\n');
+    content
+}
@@ -0,0 +1,162 @@
+//! Separate what a prompt asks for from what was pasted into it. Code,
+//! logs, stack traces and quoted output are dropped so objectives and
+//! accomplishments are derived from the request itself.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// A slash command such as `/compact` or `/review 123`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SlashCommand {
+    pub name: String,
+    pub args: Option<String>,
+}
+
+/// A prompt with pasted material removed.
+#[derive(Debug, Clone, Default)]
+pub struct CleanedPrompt {
+    /// The natural-language request that is left
+    pub intent: String,
+    /// Kinds of material that were removed: "code", "log" or "quote"
+    pub removed: Vec<&'static str>,
+}
+
+/// Recognise a prompt that is a slash command, either as typed (`/review 12`)
+/// or as Claude Code records it (`<command-name>/review</command-name>`).
+pub fn slash_command(prompt: &str) -> Option<SlashCommand> {
+    let prompt = prompt.trim();
+
+    let tagged = Regex::new(r"<command-name>/?([\w:.-]+)</command-name>").expect("valid regex");
+    if let Some(caps) = tagged.captures(prompt) {
+        let args = Regex::new(r"(?s)<command-args>(.*?)</command-args>")
+            .expect("valid regex")
+            .captures(prompt)
+            .map(|a| a[1].trim().to_string())
+            .filter(|a| !a.is_empty());
+        return Some(SlashCommand { name: caps[1].to_string(), args });
+    }
+
+    // `/usr/bin/foo is broken` is a path, not a command
+    let typed = Regex::new(r"^/([A-Za-z][\w:-]*)(?:\s+(?s)(.*))?$").expect("valid regex");
+    let caps = typed.captures(prompt)?;
+    Some(SlashCommand {
+        name: caps[1].to_string(),
+        args: caps.get(2).map(|a| a.as_str().trim().to_string()).filter(|a| !a.is_empty()),
+    })
+}
+
+/// Strip fenced code, indented code, quoted output and log or stack trace
+/// lines from a prompt.
+pub fn clean(prompt: &str) -> CleanedPrompt {
+    let log_line = Regex::new(concat!(
+        r"^\s*(?:",
+        r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}",                  // timestamps
+        r"|\[?(?:TRACE|DEBUG|INFO|WARN|WARNING|ERROR|FATAL)\]?[\s:]", // log levels
+        r"|at\s+[\w$.<>]+\s*\(",                                // JS/Java stack frames
+        r"|at\s+\S+:\d+",
+        r#"|File\s+".*",\s+line\s+\d+"#,                         // Python stack frames
+        r"|Traceback\s+\(most recent call last\)",
+        r"|[A-Z]\w*(?:Error|Exception):\s",                      // exception messages
+        r"|thread\s+'.*'\s+panicked",                           // Rust panics
+        r"|\d+:\s+(?:0x[0-9a-f]+|[\w:<>$]+$)",                   // backtrace frames
+        r"|note:\s+run\s+with\s+`?RUST_BACKTRACE",
+        r"|-->\s+\S+:\d+",                                      // compiler diagnostics
+        r"|\d*\s*\|(?:\s|$)",
+        r"|\^+",
+        r"|error(?:\[E\d+\])?:",
+        r"|warning:\s",
+        r"|\$\s+\S",                                            // shell transcripts
+        r"|npm\s+(?:ERR|WARN)!",
+        r")"
+    ))
+    .expect("valid regex");
+
+    let mut removed: Vec<&'static str> = Vec::new();
+    let mut note = |kind: &'static str| {
+        if !removed.contains(&kind) {
+            removed.push(kind);
+        }
+    };
+
+    let mut kept: Vec<&str> = Vec::new();
+    let mut in_fence = false;
+    for line in prompt.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            note("code");
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        if line.starts_with("    ") || line.starts_with('\t') {
+            note("code");
+        } else if trimmed.starts_with('>') {
+            note("quote");
+        } else if log_line.is_match(line) {
+            note("log");
+        } else if is_symbol_heavy(trimmed) {
+            note("code");
+        } else {
+            kept.push(line.trim());
+        }
+    }
+
+    let intent = kept
+        .into_iter()
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    CleanedPrompt { intent, removed }
+}
+
+/// Lines that are mostly punctuation and brackets are code or data, not prose.
+fn is_symbol_heavy(line: &str) -> bool {
+    let total = line.chars().filter(|c| !c.is_whitespace()).count();
+    if total < 12 {
+        return false;
+    }
+    let symbols = line
+        .chars()
+        .filter(|c| "{}[]()<>;=&|$#*/\\\"`".contains(*c))
+        .count();
+    symbols * 4 > total
+}
+
+/// The objective a prompt states: its intent on one line, cut to `max` characters.
+pub fn objective(intent: &str, max: usize) -> Option<String> {
+    let line = intent.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.is_empty() {
+        return None;
+    }
+    Some(crate::text::truncate_chars(&line, max).to_string())
+}
+
+/// Render the Markdown "Slash Commands" section with how often each was used.
+pub fn format_section(commands: &[SlashCommand]) -> String {
+    let mut content = String::new();
+    if commands.is_empty() {
+        return content;
+    }
+
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for command in commands {
+        match counts.iter_mut().find(|(name, _)| *name == command.name) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((&command.name, 1)),
+        }
+    }
+
+    content.push_str("### ⌨️ **Slash Commands**\n");
+    for (name, count) in counts {
+        if count == 1 {
+            content.push_str(&format!("- /{}\n", name));
+        } else {
+            content.push_str(&format!("- /{} ×{}\n", name, count));
+        }
+    }
+    content.push('\n');
+    content
+}
@@ -0,0 +1,54 @@
+use crate::config::DiaryConfig;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// A ticket or issue mentioned in a prompt or encoded in a branch name.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Reference {
+    pub kind: String,
+    pub key: String,
+    pub source: String,
+}
+
+/// Find every reference in `text` using the configured patterns.
+pub fn extract(text: &str, source: &str, config: &DiaryConfig) -> Vec<Reference> {
+    let mut references: Vec<Reference> = Vec::new();
+
+    for reference_pattern in &config.reference_patterns {
+        let Ok(regex) = Regex::new(&reference_pattern.pattern) else {
+            continue;
+        };
+
+        for caps in regex.captures_iter(text) {
+            let key = caps
+                .name("key")
+                .or_else(|| caps.get(0))
+                .map(|m| m.as_str().trim().to_string())
+                .unwrap_or_default();
+
+            if key.is_empty() || is_ignored(&key, config) {
+                continue;
+            }
+            // URLs also contain ticket-like text; keep the first kind that claimed a key
+            if references.iter().any(|r| r.key == key) {
+                continue;
+            }
+
+            references.push(Reference {
+                kind: reference_pattern.kind.clone(),
+                key,
+                source: source.to_string(),
+            });
+        }
+    }
+
+    references
+}
+
+fn is_ignored(key: &str, config: &DiaryConfig) -> bool {
+    let prefix = key.split('-').next().unwrap_or(key);
+    config
+        .ignored_reference_prefixes
+        .iter()
+        .any(|ignored| ignored.eq_ignore_ascii_case(prefix))
+}
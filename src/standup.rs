@@ -0,0 +1,105 @@
+//! The daily standup: what got done on the previous working day, what is
+//! still open, and what is blocking, grouped by project.
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::Serialize;
+
+/// One project's part of the standup.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ProjectStandup {
+    pub project: String,
+    /// Confirmed accomplishments
+    pub done: Vec<String>,
+    /// Open todos, and requests and objectives that were never confirmed
+    pub planned: Vec<String>,
+    /// Unresolved errors
+    pub blockers: Vec<String>,
+}
+
+impl ProjectStandup {
+    pub fn new(project: &str) -> Self {
+        Self { project: project.to_string(), ..Default::default() }
+    }
+}
+
+/// Add `item` to `list` unless it is already there.
+pub fn push_unique(list: &mut Vec<String>, item: String) {
+    if !list.contains(&item) {
+        list.push(item);
+    }
+}
+
+/// The last weekday before `today`, so a Monday standup covers Friday.
+pub fn previous_working_day(today: NaiveDate) -> NaiveDate {
+    let mut day = today - Duration::days(1);
+    while matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+        day -= Duration::days(1);
+    }
+    day
+}
+
+/// The request a prompt accomplishment was made from: its description
+/// without the category prefix ("Fixed code issues: fix the login bug").
+/// Short prompts leave only the prefix, and have no request text.
+pub fn request_text(description: &str) -> Option<&str> {
+    description.split_once(": ").map(|(_, request)| request)
+}
+
+/// Whether `objective` came from the prompt `request` was made from. The
+/// request keeps only the prompt's first line, cut shorter, so the
+/// objective starts with it.
+pub fn objective_matches(objective: &str, request: &str) -> bool {
+    !request.is_empty() && objective.starts_with(request)
+}
+
+/// Render the standup as Markdown, or as plain text for chat tools that
+/// don't render it.
+pub fn format(day: NaiveDate, projects: &[ProjectStandup], markdown: bool) -> String {
+    let mut content = String::new();
+    let yesterday = format!("Yesterday ({})", day.format("%A %Y-%m-%d"));
+    push_section(&mut content, &yesterday, projects, |p| &p.done, markdown);
+    push_section(&mut content, "Today", projects, |p| &p.planned, markdown);
+    push_section(&mut content, "Blockers", projects, |p| &p.blockers, markdown);
+    content
+}
+
+fn push_section(
+    content: &mut String,
+    title: &str,
+    projects: &[ProjectStandup],
+    items_of: fn(&ProjectStandup) -> &Vec<String>,
+    markdown: bool,
+) {
+    if markdown {
+        content.push_str(&format!("### {}\n", title));
+    } else {
+        content.push_str(&format!("{}:\n", title));
+    }
+
+    // A single project needs no heading of its own
+    let nested = projects.len() > 1;
+    let indent = match (markdown, nested) {
+        (true, true) => "  - ",
+        (true, false) => "- ",
+        (false, true) => "    - ",
+        (false, false) => "  - ",
+    };
+
+    let with_items: Vec<&ProjectStandup> = projects.iter().filter(|p| !items_of(p).is_empty()).collect();
+    if with_items.is_empty() {
+        content.push_str(if markdown { "- Nothing\n" } else { "  nothing\n" });
+    }
+    for project in with_items {
+        if nested {
+            if markdown {
+                content.push_str(&format!("- **{}**\n", project.project));
+            } else {
+                content.push_str(&format!("  {}:\n", project.project));
+            }
+        }
+        for item in items_of(project) {
+            content.push_str(&format!("{}{}\n", indent, item));
+        }
+    }
+    content.push('\n');
+}
@@ -0,0 +1,269 @@
+//! Usage analytics across many sessions: how tool use changes week to week,
+//! which files keep changing, where accomplishments fall, how often things
+//! go wrong, and how consistently work happens.
+
+use crate::dates::DateRange;
+use crate::format_duration;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::Serialize;
+
+/// How many files and tools the lists keep.
+pub const TOP: usize = 10;
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct UsageStats {
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    pub project: Option<String>,
+    pub sessions: usize,
+    pub active_ms: i64,
+    pub average_session_ms: i64,
+    pub working_days: usize,
+    /// Working days in a row up to today, or up to the last working day
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    /// Tool calls per week, oldest first
+    pub tool_trends: Vec<WeekTools>,
+    pub top_files: Vec<FileCount>,
+    pub categories: Vec<CategoryCount>,
+    pub errors: usize,
+    /// Errors per tool, most first; errors not tied to a tool are under "Other"
+    pub errors_by_tool: Vec<NamedCount>,
+    #[serde(skip)]
+    days: Vec<NaiveDate>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct WeekTools {
+    /// The Monday the week starts on
+    pub week: NaiveDate,
+    pub calls: i64,
+    pub tools: Vec<NamedCount>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct NamedCount {
+    pub name: String,
+    pub count: i64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FileCount {
+    pub path: String,
+    pub edits: i64,
+    pub sessions: i64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CategoryCount {
+    pub category: String,
+    pub accomplishments: i64,
+    pub percent: f64,
+}
+
+fn add_count(list: &mut Vec<NamedCount>, name: &str, count: i64) {
+    match list.iter_mut().find(|entry| entry.name == name) {
+        Some(entry) => entry.count += count,
+        None => list.push(NamedCount { name: name.to_string(), count }),
+    }
+}
+
+fn sort_counts(list: &mut [NamedCount]) {
+    list.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+}
+
+fn is_weekend(day: NaiveDate) -> bool {
+    matches!(day.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// Longest and current runs of working days with sessions. Weekends don't
+/// break a run, and count towards it when worked.
+pub fn streaks(days: &[NaiveDate], today: NaiveDate) -> (usize, usize) {
+    let mut days = days.to_vec();
+    days.sort();
+    days.dedup();
+
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for &day in &days {
+        let continues = previous.is_some_and(|previous| {
+            let mut next = previous + Duration::days(1);
+            while next < day && is_weekend(next) {
+                next += Duration::days(1);
+            }
+            next == day
+        });
+        run = if continues { run + 1 } else { 1 };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+
+    // The current run is still going if nothing has been missed since its last day
+    let current = match previous {
+        Some(last) => {
+            let mut expected = last + Duration::days(1);
+            while expected < today && is_weekend(expected) {
+                expected += Duration::days(1);
+            }
+            if expected >= today { run } else { 0 }
+        }
+        None => 0,
+    };
+    (longest, current)
+}
+
+impl UsageStats {
+    pub fn new(range: DateRange, project: Option<&str>) -> Self {
+        Self { since: range.since, until: range.until, project: project.map(str::to_string), ..Default::default() }
+    }
+
+    pub fn add_session(&mut self, day: NaiveDate, active_ms: i64) {
+        self.sessions += 1;
+        self.active_ms += active_ms;
+        self.days.push(day);
+    }
+
+    pub fn add_tool_calls(&mut self, day: NaiveDate, tool: &str, calls: i64) {
+        let week = day - Duration::days(day.weekday().num_days_from_monday() as i64);
+        let index = match self.tool_trends.iter().position(|entry| entry.week == week) {
+            Some(index) => index,
+            None => {
+                self.tool_trends.push(WeekTools { week, calls: 0, tools: Vec::new() });
+                self.tool_trends.len() - 1
+            }
+        };
+        let entry = &mut self.tool_trends[index];
+        entry.calls += calls;
+        add_count(&mut entry.tools, tool, calls);
+    }
+
+    pub fn add_file(&mut self, path: &str, edits: i64) {
+        match self.top_files.iter_mut().find(|file| file.path == path) {
+            Some(file) => {
+                file.edits += edits;
+                file.sessions += 1;
+            }
+            None => self.top_files.push(FileCount { path: path.to_string(), edits, sessions: 1 }),
+        }
+    }
+
+    pub fn add_accomplishment(&mut self, category: &str) {
+        match self.categories.iter_mut().find(|entry| entry.category == category) {
+            Some(entry) => entry.accomplishments += 1,
+            None => self.categories.push(CategoryCount { category: category.to_string(), accomplishments: 1, percent: 0.0 }),
+        }
+    }
+
+    pub fn add_error(&mut self, tool_name: Option<&str>) {
+        self.errors += 1;
+        add_count(&mut self.errors_by_tool, tool_name.unwrap_or("Other"), 1);
+    }
+
+    /// Work out averages, streaks and shares, and sort and trim the lists.
+    pub fn finish(&mut self, today: NaiveDate) {
+        if self.sessions > 0 {
+            self.average_session_ms = self.active_ms / self.sessions as i64;
+        }
+        let mut days = self.days.clone();
+        days.sort();
+        days.dedup();
+        self.working_days = days.len();
+        (self.longest_streak, self.current_streak) = streaks(&days, today);
+
+        self.tool_trends.sort_by_key(|entry| entry.week);
+        for entry in &mut self.tool_trends {
+            sort_counts(&mut entry.tools);
+        }
+        self.top_files
+            .sort_by(|a, b| b.edits.cmp(&a.edits).then(b.sessions.cmp(&a.sessions)).then_with(|| a.path.cmp(&b.path)));
+        self.top_files.truncate(TOP);
+
+        let total: i64 = self.categories.iter().map(|entry| entry.accomplishments).sum();
+        for entry in &mut self.categories {
+            entry.percent = entry.accomplishments as f64 * 100.0 / total.max(1) as f64;
+        }
+        self.categories
+            .sort_by(|a, b| b.accomplishments.cmp(&a.accomplishments).then_with(|| a.category.cmp(&b.category)));
+        sort_counts(&mut self.errors_by_tool);
+        self.errors_by_tool.truncate(TOP);
+    }
+}
+
+/// Render the stats as Markdown.
+pub fn format(stats: &UsageStats) -> String {
+    let range = DateRange { since: stats.since, until: stats.until };
+    let mut content = format!("## Usage stats: {}", range.describe());
+    if let Some(project) = &stats.project {
+        content.push_str(&format!(" ({})", project));
+    }
+    content.push_str("\n\n");
+
+    content.push_str("### 📊 **Sessions**\n");
+    content.push_str(&format!("- **Sessions**: {}\n", stats.sessions));
+    content.push_str(&format!("- **Active time**: {}\n", format_duration(stats.active_ms)));
+    content.push_str(&format!("- **Average session**: {}\n", format_duration(stats.average_session_ms)));
+    content.push_str(&format!("- **Days worked**: {}\n", stats.working_days));
+    content.push_str(&format!(
+        "- **Streak**: {} working day{} (longest {})\n\n",
+        stats.current_streak,
+        if stats.current_streak == 1 { "" } else { "s" },
+        stats.longest_streak
+    ));
+
+    content.push_str("### 🔧 **Tool Usage by Week**\n");
+    if stats.tool_trends.is_empty() {
+        content.push_str("- Nothing recorded\n");
+    }
+    let mut previous_calls = None;
+    for week in &stats.tool_trends {
+        let trend = match previous_calls {
+            Some(previous) if week.calls > previous => " ↑",
+            Some(previous) if week.calls < previous => " ↓",
+            _ => "",
+        };
+        let tools: Vec<String> = week.tools.iter().take(5).map(|tool| format!("{} {}", tool.name, tool.count)).collect();
+        content.push_str(&format!(
+            "- **Week of {}**: {} call{}{} ({})\n",
+            week.week,
+            week.calls,
+            if week.calls == 1 { "" } else { "s" },
+            trend,
+            tools.join(", ")
+        ));
+        previous_calls = Some(week.calls);
+    }
+    content.push('\n');
+
+    content.push_str("### 📝 **Most Modified Files**\n");
+    if stats.top_files.is_empty() {
+        content.push_str("- Nothing recorded\n");
+    }
+    for file in &stats.top_files {
+        content.push_str(&format!(
+            "- {}: {} edit{} in {} session{}\n",
+            file.path,
+            file.edits,
+            if file.edits == 1 { "" } else { "s" },
+            file.sessions,
+            if file.sessions == 1 { "" } else { "s" }
+        ));
+    }
+    content.push('\n');
+
+    content.push_str("### 🗂 **Accomplishments by Category**\n");
+    if stats.categories.is_empty() {
+        content.push_str("- Nothing recorded\n");
+    }
+    for entry in &stats.categories {
+        content.push_str(&format!("- **{}**: {} ({:.0}%)\n", entry.category, entry.accomplishments, entry.percent));
+    }
+    content.push('\n');
+
+    content.push_str(&format!("### ⚠️ **Errors**: {}\n", stats.errors));
+    for entry in &stats.errors_by_tool {
+        content.push_str(&format!("- {}: {}\n", entry.name, entry.count));
+    }
+    content.push('\n');
+    content
+}
@@ -0,0 +1,63 @@
+//! Accomplishment lifecycle. Work inferred from a prompt starts out as
+//! `requested` and only becomes `confirmed` once later events in the same
+//! session show it actually happened.
+
+pub const REQUESTED: &str = "requested";
+pub const IN_PROGRESS: &str = "in_progress";
+pub const CONFIRMED: &str = "confirmed";
+pub const ABANDONED: &str = "abandoned";
+
+/// Something that happened later in a session which may move an
+/// accomplishment along.
+#[derive(Debug, Clone)]
+pub enum Evidence {
+    /// Claude used a tool after the request
+    ToolActivity,
+    /// A file was successfully written or edited
+    FileEdited(String),
+    /// A test run finished with no failures
+    TestsPassed,
+    /// Claude finished responding
+    Stop,
+    /// The session ended
+    SessionEnd,
+}
+
+impl Evidence {
+    /// The status an accomplishment moves to, or `None` if this evidence
+    /// says nothing about it.
+    pub fn advance(&self, status: &str, category: &str, files: &[String]) -> Option<&'static str> {
+        let open = status == REQUESTED || status == IN_PROGRESS;
+        if !open {
+            return None;
+        }
+
+        match self {
+            Evidence::ToolActivity if status == REQUESTED => Some(IN_PROGRESS),
+            Evidence::FileEdited(path) if files.iter().any(|f| path_matches(path, f)) => Some(CONFIRMED),
+            // Starting to edit anything at all means the request is being worked on
+            Evidence::FileEdited(_) if status == REQUESTED => Some(IN_PROGRESS),
+            Evidence::TestsPassed if category == "Code Development" => Some(CONFIRMED),
+            Evidence::Stop if status == IN_PROGRESS => Some(CONFIRMED),
+            Evidence::SessionEnd if status == REQUESTED => Some(ABANDONED),
+            _ => None,
+        }
+    }
+}
+
+/// Whether an edited path is the file a prompt mentioned, allowing the prompt
+/// to use a relative or partial path (`auth.rs` matches `/repo/src/auth.rs`).
+pub fn path_matches(edited: &str, mentioned: &str) -> bool {
+    let mentioned = mentioned.trim_start_matches("./");
+    edited == mentioned || edited.ends_with(&format!("/{}", mentioned))
+}
+
+/// SQL condition selecting the accomplishments reports show by default.
+/// Rows written before statuses existed have none and are treated as confirmed.
+pub const CONFIRMED_SQL: &str = "(status IS NULL OR status = 'confirmed')";
+
+/// SQL condition leaving out accomplishments guessed from prompts once the
+/// session has completed todos, which replace them. The session id must be
+/// bound as `?1`.
+pub const TODOS_REPLACE_PROMPTS_SQL: &str = "NOT (source = 'prompt' AND EXISTS (
+    SELECT 1 FROM accomplishments todo WHERE todo.session_id = ?1 AND todo.source = 'todo'))";
@@ -0,0 +1,276 @@
+//! Local extractive summaries of assistant responses: the one or two
+//! sentences that best say what was concluded or done, picked from the
+//! response itself rather than generated.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::text::ellipsize;
+
+const MAX_SENTENCES: usize = 2;
+const MAX_SUMMARY_CHARS: usize = 300;
+
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "can", "do", "for", "from", "has", "have",
+    "i", "if", "in", "into", "is", "it", "its", "it's", "let", "me", "my", "not", "now", "of", "on",
+    "or", "so", "that", "the", "then", "there", "this", "to", "was", "we", "were", "will", "with",
+    "you", "your", "i'll", "i've", "here", "which", "also", "should", "would", "these", "those",
+];
+
+/// Phrases that usually introduce the outcome of a piece of work.
+const CUE_PHRASES: &[&str] = &[
+    "the issue", "the problem", "root cause", "caused by", "the fix", "fixed", "i've", "i have",
+    "added", "updated", "implemented", "removed", "renamed", "refactored", "now ", "all tests",
+    "tests pass", "in summary", "to summarize", "summary", "the result", "done", "completed",
+    "resolved", "turns out", "because",
+];
+
+/// Phrases that open conversational filler rather than content.
+const FILLER_OPENINGS: &[&str] = &[
+    "let me", "i'll ", "i will", "sure", "great", "okay", "ok,", "now let me", "first, let me",
+    "let's", "perfect", "excellent",
+];
+
+/// Summarize a response in at most two sentences, or `None` if it has no prose.
+pub fn summarize(text: &str) -> Option<String> {
+    let prose = strip_code(text);
+    let (headings, bullets, paragraphs) = split_structure(&prose);
+
+    let sentences: Vec<String> = paragraphs
+        .iter()
+        .flat_map(|p| split_sentences(p))
+        .filter(|s| s.split_whitespace().count() >= 3)
+        .collect();
+
+    if sentences.is_empty() {
+        // A response that is all headings and bullets reads best as its list
+        if bullets.is_empty() {
+            return headings.first().map(|h| ellipsize(h, MAX_SUMMARY_CHARS));
+        }
+        let items: Vec<&str> = bullets.iter().take(3).map(|b| b.as_str()).collect();
+        let summary = match headings.first() {
+            Some(heading) => format!("{}: {}", heading, items.join("; ")),
+            None => items.join("; "),
+        };
+        return Some(ellipsize(&summary, MAX_SUMMARY_CHARS));
+    }
+
+    let frequencies = word_frequencies(sentences.iter().map(|s| s.as_str()).chain(bullets.iter().map(|b| b.as_str())));
+    let mut scored: Vec<(usize, f64)> = sentences
+        .iter()
+        .enumerate()
+        .map(|(index, sentence)| (index, score(sentence, index, sentences.len(), &frequencies)))
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut chosen: Vec<usize> = scored.iter().take(MAX_SENTENCES).map(|(index, _)| *index).collect();
+    chosen.sort_unstable();
+    let summary = chosen
+        .iter()
+        .map(|index| sentences[*index].as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Some(ellipsize(&summary, MAX_SUMMARY_CHARS))
+}
+
+/// Remove fenced and indented code blocks, keeping indented list items.
+fn strip_code(text: &str) -> String {
+    let mut prose = String::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        let indented = line.starts_with("    ") || line.starts_with('\t');
+        if in_fence || (indented && bullet_text(trimmed).is_none()) {
+            continue;
+        }
+        prose.push_str(line);
+        prose.push('\n');
+    }
+    prose
+}
+
+/// Split Markdown into headings, bullet items and prose paragraphs.
+fn split_structure(text: &str) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let mut headings = Vec::new();
+    let mut bullets = Vec::new();
+    let mut paragraphs = Vec::new();
+    let mut current = String::new();
+
+    fn flush(current: &mut String, paragraphs: &mut Vec<String>) {
+        if !current.trim().is_empty() {
+            paragraphs.push(current.trim().to_string());
+        }
+        current.clear();
+    }
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('|') || trimmed.starts_with("---") {
+            flush(&mut current, &mut paragraphs);
+        } else if let Some(heading) = trimmed.strip_prefix('#') {
+            flush(&mut current, &mut paragraphs);
+            headings.push(clean_inline(heading.trim_start_matches('#').trim()));
+        } else if let Some(item) = bullet_text(trimmed) {
+            flush(&mut current, &mut paragraphs);
+            bullets.push(clean_inline(item));
+        } else {
+            current.push(' ');
+            current.push_str(&clean_inline(trimmed));
+        }
+    }
+    flush(&mut current, &mut paragraphs);
+
+    (headings, bullets, paragraphs)
+}
+
+fn bullet_text(line: &str) -> Option<&str> {
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(rest) = line.strip_prefix(marker) {
+            return Some(rest.trim());
+        }
+    }
+    // Numbered items such as "1. " or "2) "
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        let rest = &line[digits..];
+        if let Some(rest) = rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") ")) {
+            return Some(rest.trim());
+        }
+    }
+    None
+}
+
+/// Drop Markdown emphasis markers so summaries read as plain text.
+fn clean_inline(text: &str) -> String {
+    text.replace("**", "").replace("__", "").trim().to_string()
+}
+
+/// Split a paragraph into sentences, treating a full stop, question mark or
+/// exclamation mark followed by whitespace as the end of a sentence, except
+/// after common abbreviations and inside file names such as `main.rs`.
+fn split_sentences(paragraph: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+    let chars: Vec<char> = paragraph.chars().collect();
+
+    for (i, c) in chars.iter().enumerate() {
+        current.push(*c);
+        let at_boundary = matches!(c, '.' | '!' | '?')
+            && chars.get(i + 1).is_none_or(|next| next.is_whitespace());
+        if at_boundary {
+            let last_word = current.split_whitespace().last().unwrap_or("").to_lowercase();
+            if ["e.g.", "i.e.", "etc.", "vs.", "approx."].contains(&last_word.as_str()) {
+                continue;
+            }
+            sentences.push(current.trim().to_string());
+            current.clear();
+        }
+    }
+    if !current.trim().is_empty() {
+        sentences.push(current.trim().to_string());
+    }
+    sentences
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'' && c != '_')
+        .filter(|w| w.chars().count() > 2)
+        .map(|w| w.to_lowercase())
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+}
+
+fn word_frequencies<'a>(texts: impl Iterator<Item = &'a str>) -> HashMap<String, f64> {
+    let mut counts: HashMap<String, f64> = HashMap::new();
+    for text in texts {
+        for word in words(text) {
+            *counts.entry(word).or_default() += 1.0;
+        }
+    }
+    let max = counts.values().cloned().fold(1.0, f64::max);
+    for value in counts.values_mut() {
+        *value /= max;
+    }
+    counts
+}
+
+fn score(sentence: &str, index: usize, total: usize, frequencies: &HashMap<String, f64>) -> f64 {
+    let sentence_words: Vec<String> = words(sentence).collect();
+    if sentence_words.is_empty() {
+        return 0.0;
+    }
+    let mut score = sentence_words
+        .iter()
+        .map(|w| frequencies.get(w).copied().unwrap_or(0.0))
+        .sum::<f64>()
+        / sentence_words.len() as f64;
+
+    let lower = sentence.to_lowercase();
+    if CUE_PHRASES.iter().any(|cue| lower.contains(cue)) {
+        score += 0.5;
+    }
+    if FILLER_OPENINGS.iter().any(|filler| lower.starts_with(filler)) {
+        score -= 0.6;
+    }
+    if sentence.ends_with('?') {
+        score -= 0.4;
+    }
+    // Conclusions tend to come last, context first
+    if index + 1 == total {
+        score += 0.3;
+    } else if index == 0 {
+        score += 0.15;
+    }
+    let length = sentence.split_whitespace().count();
+    if length > 45 {
+        score -= 0.3;
+    }
+    score
+}
+
+/// The text of the assistant's latest reply in a Claude Code transcript:
+/// every assistant text block since the last prompt the user typed.
+pub fn last_assistant_text(transcript_path: &Path) -> Option<String> {
+    let file = std::fs::File::open(transcript_path).ok()?;
+    let mut reply: Vec<String> = Vec::new();
+
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        let content = entry.get("message").and_then(|m| m.get("content"));
+        match entry.get("type").and_then(|t| t.as_str()) {
+            Some("user") if is_typed_prompt(content) => reply.clear(),
+            Some("assistant") => {
+                let blocks = content.and_then(|c| c.as_array()).cloned().unwrap_or_default();
+                reply.extend(
+                    blocks
+                        .iter()
+                        .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("text"))
+                        .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+                        .map(|text| text.to_string()),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let text = reply.join("\n\n");
+    (!text.trim().is_empty()).then_some(text)
+}
+
+/// Tool results are also recorded as user messages; only text is a prompt.
+fn is_typed_prompt(content: Option<&serde_json::Value>) -> bool {
+    match content {
+        Some(serde_json::Value::String(_)) => true,
+        Some(serde_json::Value::Array(blocks)) => blocks
+            .iter()
+            .any(|block| block.get("type").and_then(|t| t.as_str()) == Some("text")),
+        _ => false,
+    }
+}
@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+
+pub const LANGUAGE: &str = "language";
+pub const FRAMEWORK: &str = "framework";
+pub const TOOL: &str = "tool";
+
+/// A language, framework or tool a session worked with, and what gave it away.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Technology {
+    pub name: String,
+    pub kind: String,
+    /// The file, manifest or command it was detected from
+    pub evidence: String,
+}
+
+/// File extensions, as (extension, technology, kind).
+const EXTENSIONS: &[(&str, &str, &str)] = &[
+    ("rs", "Rust", LANGUAGE),
+    ("ts", "TypeScript", LANGUAGE),
+    ("tsx", "TypeScript", LANGUAGE),
+    ("mts", "TypeScript", LANGUAGE),
+    ("js", "JavaScript", LANGUAGE),
+    ("jsx", "JavaScript", LANGUAGE),
+    ("mjs", "JavaScript", LANGUAGE),
+    ("cjs", "JavaScript", LANGUAGE),
+    ("py", "Python", LANGUAGE),
+    ("ipynb", "Python", LANGUAGE),
+    ("go", "Go", LANGUAGE),
+    ("tf", "Terraform", LANGUAGE),
+    ("tfvars", "Terraform", LANGUAGE),
+    ("rb", "Ruby", LANGUAGE),
+    ("java", "Java", LANGUAGE),
+    ("kt", "Kotlin", LANGUAGE),
+    ("kts", "Kotlin", LANGUAGE),
+    ("swift", "Swift", LANGUAGE),
+    ("c", "C", LANGUAGE),
+    ("h", "C", LANGUAGE),
+    ("cpp", "C++", LANGUAGE),
+    ("cc", "C++", LANGUAGE),
+    ("cxx", "C++", LANGUAGE),
+    ("hpp", "C++", LANGUAGE),
+    ("cs", "C#", LANGUAGE),
+    ("php", "PHP", LANGUAGE),
+    ("scala", "Scala", LANGUAGE),
+    ("ex", "Elixir", LANGUAGE),
+    ("exs", "Elixir", LANGUAGE),
+    ("dart", "Dart", LANGUAGE),
+    ("lua", "Lua", LANGUAGE),
+    ("zig", "Zig", LANGUAGE),
+    ("sql", "SQL", LANGUAGE),
+    ("sh", "Shell", LANGUAGE),
+    ("bash", "Shell", LANGUAGE),
+    ("zsh", "Shell", LANGUAGE),
+    ("html", "HTML", LANGUAGE),
+    ("css", "CSS", LANGUAGE),
+    ("scss", "CSS", LANGUAGE),
+    ("sass", "CSS", LANGUAGE),
+    ("vue", "Vue", FRAMEWORK),
+    ("svelte", "Svelte", FRAMEWORK),
+];
+
+/// Manifest and build files, as (file name, technology, kind).
+const MANIFESTS: &[(&str, &str, &str)] = &[
+    ("Cargo.toml", "Rust", LANGUAGE),
+    ("package.json", "Node.js", TOOL),
+    ("tsconfig.json", "TypeScript", LANGUAGE),
+    ("go.mod", "Go", LANGUAGE),
+    ("pyproject.toml", "Python", LANGUAGE),
+    ("requirements.txt", "Python", LANGUAGE),
+    ("setup.py", "Python", LANGUAGE),
+    ("Pipfile", "Python", LANGUAGE),
+    ("Gemfile", "Ruby", LANGUAGE),
+    ("pom.xml", "Java", LANGUAGE),
+    ("build.gradle", "Java", LANGUAGE),
+    ("build.gradle.kts", "Kotlin", LANGUAGE),
+    ("composer.json", "PHP", LANGUAGE),
+    ("mix.exs", "Elixir", LANGUAGE),
+    ("pubspec.yaml", "Dart", LANGUAGE),
+    ("Dockerfile", "Docker", TOOL),
+    ("docker-compose.yml", "Docker", TOOL),
+    ("docker-compose.yaml", "Docker", TOOL),
+    ("compose.yaml", "Docker", TOOL),
+    ("Chart.yaml", "Kubernetes", TOOL),
+];
+
+/// Dependencies that identify a framework when they appear in a manifest.
+const FRAMEWORK_DEPENDENCIES: &[(&str, &str)] = &[
+    ("\"react\"", "React"),
+    ("\"next\"", "Next.js"),
+    ("\"vue\"", "Vue"),
+    ("\"svelte\"", "Svelte"),
+    ("\"@angular/core\"", "Angular"),
+    ("\"express\"", "Express"),
+    ("\"@nestjs/core\"", "NestJS"),
+    ("django", "Django"),
+    ("flask", "Flask"),
+    ("fastapi", "FastAPI"),
+    ("actix-web", "Actix Web"),
+    ("axum", "Axum"),
+    ("tokio", "Tokio"),
+    ("rocket", "Rocket"),
+    ("gin-gonic/gin", "Gin"),
+    ("rails", "Rails"),
+    ("spring-boot", "Spring Boot"),
+];
+
+/// Commands that show which toolchain was in use, as (program, technology, kind).
+const COMMANDS: &[(&str, &str, &str)] = &[
+    ("cargo", "Rust", LANGUAGE),
+    ("rustc", "Rust", LANGUAGE),
+    ("npm", "Node.js", TOOL),
+    ("npx", "Node.js", TOOL),
+    ("yarn", "Node.js", TOOL),
+    ("pnpm", "Node.js", TOOL),
+    ("node", "Node.js", TOOL),
+    ("bun", "Bun", TOOL),
+    ("deno", "Deno", TOOL),
+    ("tsc", "TypeScript", LANGUAGE),
+    ("python", "Python", LANGUAGE),
+    ("python3", "Python", LANGUAGE),
+    ("pip", "Python", LANGUAGE),
+    ("pytest", "Python", LANGUAGE),
+    ("poetry", "Python", LANGUAGE),
+    ("uv", "Python", LANGUAGE),
+    ("go", "Go", LANGUAGE),
+    ("terraform", "Terraform", LANGUAGE),
+    ("tofu", "Terraform", LANGUAGE),
+    ("docker", "Docker", TOOL),
+    ("docker-compose", "Docker", TOOL),
+    ("kubectl", "Kubernetes", TOOL),
+    ("helm", "Kubernetes", TOOL),
+    ("bundle", "Ruby", LANGUAGE),
+    ("rails", "Rails", FRAMEWORK),
+    ("mvn", "Java", LANGUAGE),
+    ("gradle", "Java", LANGUAGE),
+    ("dotnet", ".NET", FRAMEWORK),
+    ("mix", "Elixir", LANGUAGE),
+    ("flutter", "Flutter", FRAMEWORK),
+];
+
+fn technology(name: &str, kind: &str, evidence: &str) -> Technology {
+    Technology {
+        name: name.to_string(),
+        kind: kind.to_string(),
+        evidence: evidence.to_string(),
+    }
+}
+
+/// Technologies implied by a file's extension or, for manifests, its name.
+pub fn from_path(path: &str) -> Vec<Technology> {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    let mut found = Vec::new();
+
+    if let Some((_, tech, kind)) = MANIFESTS.iter().find(|(manifest, _, _)| *manifest == name) {
+        found.push(technology(tech, kind, path));
+    }
+    if let Some((_, extension)) = name.rsplit_once('.') {
+        let extension = extension.to_ascii_lowercase();
+        if let Some((_, tech, kind)) = EXTENSIONS.iter().find(|(ext, _, _)| *ext == extension) {
+            found.push(technology(tech, kind, path));
+        }
+    }
+    found
+}
+
+/// Frameworks named as dependencies in text written to a manifest file.
+pub fn from_manifest_content(path: &str, content: &str) -> Vec<Technology> {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    if !MANIFESTS.iter().any(|(manifest, _, _)| *manifest == name) {
+        return Vec::new();
+    }
+    let content = content.to_lowercase();
+    FRAMEWORK_DEPENDENCIES
+        .iter()
+        .filter(|(dependency, _)| content.contains(dependency))
+        .map(|(_, framework)| technology(framework, FRAMEWORK, path))
+        .collect()
+}
+
+/// Technologies whose toolchain a shell command ran, looking at the program
+/// at the start of each command in a pipeline or `&&` chain.
+pub fn from_command(command: &str) -> Vec<Technology> {
+    let mut found: Vec<Technology> = Vec::new();
+    for part in command.split(['&', ';', '|', '\n']) {
+        // Skip environment assignments such as `RUST_LOG=debug cargo test`
+        let Some(program) = part.split_whitespace().find(|word| !word.contains('=')) else {
+            continue;
+        };
+        let program = program.rsplit('/').next().unwrap_or(program);
+        if let Some((_, tech, kind)) = COMMANDS.iter().find(|(name, _, _)| *name == program) {
+            if !found.iter().any(|t| t.name == *tech) {
+                found.push(technology(tech, kind, part.trim()));
+            }
+        }
+    }
+    found
+}
+
+/// Add newly detected technologies to a session's list, keeping the first
+/// piece of evidence for each.
+pub fn merge(into: &mut Vec<Technology>, found: Vec<Technology>) {
+    for tech in found {
+        if !into.iter().any(|t| t.name == tech.name) {
+            into.push(tech);
+        }
+    }
+}
+
+/// Render the Markdown "Tech Stack" section for one session.
+pub fn format_section(technologies: &[Technology]) -> String {
+    let mut content = String::new();
+    if technologies.is_empty() {
+        return content;
+    }
+
+    content.push_str("### 🧰 **Tech Stack**\n");
+    for kind in [LANGUAGE, FRAMEWORK, TOOL] {
+        let names: Vec<&str> = technologies
+            .iter()
+            .filter(|t| t.kind == kind)
+            .map(|t| t.name.as_str())
+            .collect();
+        if !names.is_empty() {
+            let label = match kind {
+                LANGUAGE => "Languages",
+                FRAMEWORK => "Frameworks",
+                _ => "Tools",
+            };
+            content.push_str(&format!("- **{}:** {}\n", label, names.join(", ")));
+        }
+    }
+    content.push('\n');
+    content
+}
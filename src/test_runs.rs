@@ -0,0 +1,317 @@
+//! Test runs parsed from the output of test commands run through Bash.
+
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Outcome of a single test-runner invocation, parsed from the tool output.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TestRun {
+    pub runner: String,
+    pub passed: u32,
+    pub failed: u32,
+    pub skipped: u32,
+    pub failing_tests: Vec<String>,
+    pub duration_ms: Option<u64>,
+    pub run_at: DateTime<Local>,
+}
+
+impl TestRun {
+    fn new(runner: &str) -> Self {
+        Self {
+            runner: runner.to_string(),
+            passed: 0,
+            failed: 0,
+            skipped: 0,
+            failing_tests: Vec::new(),
+            duration_ms: None,
+            run_at: Local::now(),
+        }
+    }
+
+    pub fn is_green(&self) -> bool {
+        self.failed == 0
+    }
+
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{}: {} passed, {} failed, {} skipped",
+            self.runner, self.passed, self.failed, self.skipped
+        );
+        if let Some(duration) = self.duration_ms {
+            summary.push_str(&format!(" in {:.2}s", duration as f64 / 1000.0));
+        }
+        summary
+    }
+}
+
+/// Parse the output of a test command. The command has to run tests: either
+/// it names the runner, or it runs a `test` script (`npm test`, `make test`)
+/// and the runner is told from the shape of the output.
+pub fn parse_test_output(command: &str, output: &str) -> Option<TestRun> {
+    let runner = detect_runner_from_command(command).or_else(|| {
+        if runs_test_script(command) {
+            detect_runner_from_output(output)
+        } else {
+            None
+        }
+    })?;
+
+    let run = match runner {
+        "cargo" => parse_cargo(output),
+        "pytest" => parse_pytest(output),
+        "jest" => parse_jest(output),
+        "vitest" => parse_vitest(output),
+        "go" => parse_go(output),
+        _ => None,
+    }?;
+
+    // A runner that was invoked but reported nothing is not a test run
+    if run.passed + run.failed + run.skipped == 0 {
+        return None;
+    }
+    Some(run)
+}
+
+fn detect_runner_from_command(command: &str) -> Option<&'static str> {
+    let command = command.to_lowercase();
+    if command.contains("cargo test") || command.contains("cargo nextest") {
+        Some("cargo")
+    } else if command.contains("pytest") {
+        Some("pytest")
+    } else if command.contains("vitest") {
+        Some("vitest")
+    } else if command.contains("jest") {
+        Some("jest")
+    } else if command.contains("go test") {
+        Some("go")
+    } else {
+        None
+    }
+}
+
+fn runs_test_script(command: &str) -> bool {
+    command.split_whitespace().any(|word| word == "test" || word.starts_with("test:"))
+}
+
+fn detect_runner_from_output(output: &str) -> Option<&'static str> {
+    if output.contains("test result: ") {
+        Some("cargo")
+    } else if output.contains("test session starts") {
+        Some("pytest")
+    } else if output.contains("Test Files ") {
+        Some("vitest")
+    } else if output.contains("Test Suites:") {
+        Some("jest")
+    } else if output.contains("--- PASS:") || output.contains("--- FAIL:") {
+        Some("go")
+    } else {
+        None
+    }
+}
+
+/// Extract `<number> <label>` from a summary line, e.g. "3 passed". The
+/// label may be the start of a longer word, so "error" also counts "3 errors".
+fn count_of(line: &str, label: &str) -> u32 {
+    static COUNT: OnceLock<Regex> = OnceLock::new();
+    let re = COUNT.get_or_init(|| Regex::new(r"(\d+) (\w+)").expect("valid regex"));
+    re.captures_iter(line)
+        .find(|caps| caps[2].starts_with(label))
+        .and_then(|caps| caps[1].parse().ok())
+        .unwrap_or(0)
+}
+
+/// Parse a duration such as "0.52s", "1.234 s" or "850ms" into milliseconds.
+fn parse_duration_ms(text: &str) -> Option<u64> {
+    static DURATION: OnceLock<Regex> = OnceLock::new();
+    let re = DURATION.get_or_init(|| Regex::new(r"(\d+(?:\.\d+)?)\s*(ms|s)\b").expect("valid regex"));
+    let caps = re.captures(text)?;
+    let value: f64 = caps[1].parse().ok()?;
+    let ms = if &caps[2] == "ms" { value } else { value * 1000.0 };
+    Some(ms.round() as u64)
+}
+
+fn parse_cargo(output: &str) -> Option<TestRun> {
+    let mut run = TestRun::new("cargo");
+    let mut total_ms = 0u64;
+    let mut saw_duration = false;
+
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("test result: ") {
+            run.passed += count_of(rest, "passed");
+            run.failed += count_of(rest, "failed");
+            run.skipped += count_of(rest, "ignored");
+            if let Some(ms) = rest.split("finished in ").nth(1).and_then(parse_duration_ms) {
+                total_ms += ms;
+                saw_duration = true;
+            }
+        } else if let Some(name) = line
+            .strip_prefix("test ")
+            .and_then(|rest| rest.strip_suffix(" ... FAILED"))
+        {
+            run.failing_tests.push(name.to_string());
+        }
+    }
+
+    if saw_duration {
+        run.duration_ms = Some(total_ms);
+    }
+    Some(run)
+}
+
+fn parse_pytest(output: &str) -> Option<TestRun> {
+    let mut run = TestRun::new("pytest");
+
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("FAILED ") {
+            let name = rest.split(" - ").next().unwrap_or(rest).trim();
+            run.failing_tests.push(name.to_string());
+        }
+    }
+
+    // The final "=== 2 failed, 10 passed in 0.12s ===" line carries the totals
+    let summary = output
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| line.starts_with('=') && line.contains(" in ") && line.chars().any(|c| c.is_ascii_digit()))?;
+
+    run.passed = count_of(summary, "passed");
+    run.failed = count_of(summary, "failed") + count_of(summary, "error");
+    run.skipped = count_of(summary, "skipped") + count_of(summary, "xfailed");
+    run.duration_ms = summary.rsplit(" in ").next().and_then(parse_duration_ms);
+    Some(run)
+}
+
+fn parse_jest(output: &str) -> Option<TestRun> {
+    let mut run = TestRun::new("jest");
+    let mut found_summary = false;
+
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("Tests:") {
+            run.passed = count_of(rest, "passed");
+            run.failed = count_of(rest, "failed");
+            run.skipped = count_of(rest, "skipped") + count_of(rest, "todo");
+            found_summary = true;
+        } else if let Some(rest) = line.strip_prefix("Time:") {
+            run.duration_ms = parse_duration_ms(rest);
+        } else if let Some(name) = line.strip_prefix("● ") {
+            let name = name.trim();
+            if !run.failing_tests.iter().any(|t| t == name) {
+                run.failing_tests.push(name.to_string());
+            }
+        }
+    }
+
+    found_summary.then_some(run)
+}
+
+fn parse_vitest(output: &str) -> Option<TestRun> {
+    let mut run = TestRun::new("vitest");
+    let mut found_summary = false;
+
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("Tests ") {
+            run.passed = count_of(rest, "passed");
+            run.failed = count_of(rest, "failed");
+            run.skipped = count_of(rest, "skipped") + count_of(rest, "todo");
+            found_summary = true;
+        } else if let Some(rest) = line.strip_prefix("Duration ") {
+            run.duration_ms = parse_duration_ms(rest);
+        } else if let Some(name) = line.strip_prefix("FAIL ") {
+            run.failing_tests.push(name.trim().to_string());
+        }
+    }
+
+    found_summary.then_some(run)
+}
+
+fn parse_go(output: &str) -> Option<TestRun> {
+    let mut run = TestRun::new("go");
+    let mut package_ok = 0;
+    let mut package_failed = 0;
+    let mut total_ms = 0u64;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("--- PASS: ") {
+            run.passed += 1;
+        } else if let Some(rest) = trimmed.strip_prefix("--- FAIL: ") {
+            run.failed += 1;
+            let name = rest.split_whitespace().next().unwrap_or(rest);
+            run.failing_tests.push(name.to_string());
+        } else if trimmed.starts_with("--- SKIP: ") {
+            run.skipped += 1;
+        } else if line.starts_with("ok ") || line.starts_with("ok\t") {
+            package_ok += 1;
+            total_ms += line.split_whitespace().last().and_then(parse_duration_ms).unwrap_or(0);
+        } else if line.starts_with("FAIL\t") || line.starts_with("FAIL ") {
+            package_failed += 1;
+            total_ms += line.split_whitespace().last().and_then(parse_duration_ms).unwrap_or(0);
+        }
+    }
+
+    // Without -v go only reports per package, so count packages instead
+    if run.passed + run.failed + run.skipped == 0 {
+        run.passed = package_ok;
+        run.failed = package_failed;
+    }
+    if total_ms > 0 {
+        run.duration_ms = Some(total_ms);
+    }
+    Some(run)
+}
+
+/// Describe how the test suite moved over the course of a session, e.g.
+/// "Tests went from 3 failing to green".
+pub fn describe_trend(runs: &[TestRun]) -> Option<String> {
+    let first = runs.first()?;
+    let last = runs.last()?;
+
+    if runs.len() == 1 {
+        return Some(if last.is_green() {
+            format!("Tests passed ({} passing)", last.passed)
+        } else {
+            format!("Tests failing ({} failing)", last.failed)
+        });
+    }
+
+    Some(match (first.is_green(), last.is_green()) {
+        (false, true) => format!("Tests went from {} failing to green", first.failed),
+        (true, false) => format!("Tests went from green to {} failing", last.failed),
+        (true, true) => format!("Tests stayed green across {} runs", runs.len()),
+        (false, false) if first.failed == last.failed => {
+            format!("Tests still failing ({} failing across {} runs)", last.failed, runs.len())
+        }
+        (false, false) => format!("Tests went from {} failing to {} failing", first.failed, last.failed),
+    })
+}
+
+/// Render the Markdown "Test Runs" section shared by the live and stored reports.
+pub fn format_section(runs: &[TestRun]) -> String {
+    let mut content = String::new();
+    if runs.is_empty() {
+        return content;
+    }
+
+    content.push_str("### 🧪 **Test Runs**\n");
+    if let Some(trend) = describe_trend(runs) {
+        content.push_str(&format!("_{}_\n", trend));
+    }
+    for run in runs {
+        content.push_str(&format!("- {} {}\n", run.run_at.format("%H:%M:%S"), run.summary()));
+        for name in run.failing_tests.iter().take(10) {
+            content.push_str(&format!("  - ❌ {}\n", name));
+        }
+        if run.failing_tests.len() > 10 {
+            content.push_str(&format!("  - ...and {} more\n", run.failing_tests.len() - 10));
+        }
+    }
+    content.push('\n');
+    content
+}
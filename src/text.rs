@@ -0,0 +1,18 @@
+//! Text helpers that count characters rather than bytes, so prompts in any
+//! language can be shortened without splitting a multi-byte character.
+
+/// The first `max` characters of `text`.
+pub fn truncate_chars(text: &str, max: usize) -> &str {
+    match text.char_indices().nth(max) {
+        Some((index, _)) => &text[..index],
+        None => text,
+    }
+}
+
+/// Shorten text to at most `max` characters, marking the cut with "...".
+pub fn ellipsize(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    format!("{}...", truncate_chars(text, max.saturating_sub(3)).trim_end())
+}
@@ -0,0 +1,129 @@
+//! Billable hours per day and project, as CSV for timesheet and invoicing
+//! tools.
+
+use crate::config::{ClientMapping, TimesheetConfig};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::path::Path;
+
+/// One line of the timesheet: a project's work on one day.
+#[derive(Serialize, Debug, Clone)]
+pub struct TimesheetRow {
+    pub date: NaiveDate,
+    pub project: String,
+    pub client: Option<String>,
+    pub code: Option<String>,
+    /// Active time rounded to the configured increment
+    pub minutes: i64,
+    pub hours: f64,
+    pub description: String,
+}
+
+/// Work on one project on one day, before rounding.
+#[derive(Debug, Clone)]
+pub struct DayWork {
+    pub date: NaiveDate,
+    pub project: String,
+    pub client: Option<ClientMapping>,
+    pub duration_ms: i64,
+    pub descriptions: Vec<String>,
+}
+
+/// Add a session to the day and project it belongs to.
+pub fn add_session(
+    days: &mut Vec<DayWork>,
+    date: NaiveDate,
+    project: String,
+    client: Option<&ClientMapping>,
+    duration_ms: i64,
+    descriptions: Vec<String>,
+) {
+    let client_path = client.map(|c| c.path.as_str());
+    let index = match days.iter().position(|work| {
+        work.date == date && work.project == project && work.client.as_ref().map(|c| c.path.as_str()) == client_path
+    }) {
+        Some(index) => index,
+        None => {
+            days.push(DayWork { date, project, client: client.cloned(), duration_ms: 0, descriptions: Vec::new() });
+            days.len() - 1
+        }
+    };
+    let work = &mut days[index];
+    work.duration_ms += duration_ms;
+    for description in descriptions {
+        if !work.descriptions.contains(&description) {
+            work.descriptions.push(description);
+        }
+    }
+}
+
+impl DayWork {
+    /// The timesheet row, or `None` when the time rounds down to nothing.
+    pub fn into_row(self, config: &TimesheetConfig) -> Option<TimesheetRow> {
+        let minutes = round_minutes(self.duration_ms, config);
+        (minutes > 0).then(|| TimesheetRow {
+            date: self.date,
+            project: self.project,
+            client: self.client.as_ref().map(|c| c.client.clone()),
+            code: self.client.and_then(|c| c.code),
+            minutes,
+            hours: minutes as f64 / 60.0,
+            description: self.descriptions.join("; "),
+        })
+    }
+}
+
+/// Round a duration to the configured increment, in minutes.
+pub fn round_minutes(duration_ms: i64, config: &TimesheetConfig) -> i64 {
+    let increment = config.increment_minutes.max(1) as i64 * 60_000;
+    let increments = match config.rounding.as_str() {
+        "up" => (duration_ms + increment - 1) / increment,
+        "down" => duration_ms / increment,
+        _ => (duration_ms + increment / 2) / increment,
+    };
+    increments * config.increment_minutes.max(1) as i64
+}
+
+/// The client a session's working directory is billed to: the mapping with
+/// the longest path that contains it. `~` in mapped paths is the home directory.
+pub fn client_for<'a>(cwd: &str, clients: &'a [ClientMapping]) -> Option<&'a ClientMapping> {
+    let home = dirs::home_dir();
+    clients
+        .iter()
+        .filter_map(|mapping| {
+            let path = match (mapping.path.strip_prefix("~/"), &home) {
+                (Some(rest), Some(home)) => home.join(rest),
+                _ => Path::new(&mapping.path).to_path_buf(),
+            };
+            Path::new(cwd).starts_with(&path).then(|| (path.components().count(), mapping))
+        })
+        .max_by_key(|(depth, _)| *depth)
+        .map(|(_, mapping)| mapping)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Render rows as CSV with a header line.
+pub fn to_csv(rows: &[TimesheetRow]) -> String {
+    let mut content = String::from("date,project,client,code,hours,description\n");
+    for row in rows {
+        let fields = [
+            row.date.to_string(),
+            row.project.clone(),
+            row.client.clone().unwrap_or_default(),
+            row.code.clone().unwrap_or_default(),
+            format!("{:.2}", row.hours),
+            row.description.clone(),
+        ];
+        let line: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        content.push_str(&line.join(","));
+        content.push('\n');
+    }
+    content
+}
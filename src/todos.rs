@@ -0,0 +1,70 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// One entry of a `TodoWrite` task list.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TodoItem {
+    pub content: String,
+    pub status: String,
+}
+
+/// A todo moving between statuses (pending, in_progress, completed).
+/// `from` is `None` the first time an item is seen.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TodoTransition {
+    pub content: String,
+    pub from: Option<String>,
+    pub to: String,
+    pub changed_at: DateTime<Local>,
+}
+
+pub const COMPLETED: &str = "completed";
+
+/// Read the task list out of a `TodoWrite` tool input.
+pub fn parse_todo_write(input: &serde_json::Value) -> Vec<TodoItem> {
+    let Some(todos) = input.get("todos").and_then(|t| t.as_array()) else {
+        return Vec::new();
+    };
+
+    todos
+        .iter()
+        .filter_map(|todo| {
+            let content = todo.get("content").and_then(|c| c.as_str())?.trim();
+            if content.is_empty() {
+                return None;
+            }
+            let status = todo
+                .get("status")
+                .and_then(|s| s.as_str())
+                .unwrap_or("pending");
+            Some(TodoItem {
+                content: content.to_string(),
+                status: status.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn status_icon(status: &str) -> &'static str {
+    match status {
+        COMPLETED => "✅",
+        "in_progress" => "🔄",
+        _ => "⏳",
+    }
+}
+
+/// Render the Markdown "Tasks" section shared by the live and stored reports.
+pub fn format_section(todos: &[TodoItem]) -> String {
+    let mut content = String::new();
+    if todos.is_empty() {
+        return content;
+    }
+
+    let completed = todos.iter().filter(|t| t.status == COMPLETED).count();
+    content.push_str(&format!("### 📋 **Tasks** _({}/{} completed)_\n", completed, todos.len()));
+    for todo in todos {
+        content.push_str(&format!("- {} {}\n", status_icon(&todo.status), todo.content));
+    }
+    content.push('\n');
+    content
+}
@@ -0,0 +1,119 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+// Long enough to identify the error, short enough to read in a list
+const EXCERPT_CHARS: usize = 150;
+
+/// A tool call that did not succeed, recorded as an issue.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ToolFailure {
+    pub tool_name: String,
+    pub file_path: Option<String>,
+    pub excerpt: String,
+    pub failed_at: DateTime<Local>,
+}
+
+impl ToolFailure {
+    /// The issue text shown in reports, e.g. `Edit failed on src/lib.rs: String not found`.
+    pub fn issue_text(&self) -> String {
+        match &self.file_path {
+            Some(path) => format!("{} failed on {}: {}", self.tool_name, path, self.excerpt),
+            None => format!("{} failed: {}", self.tool_name, self.excerpt),
+        }
+    }
+}
+
+/// Whether a hook `tool_response` reports an error. Claude Code marks failures
+/// with `is_error`, `success: false`, an `error` field or a `<tool_use_error>` tag.
+pub fn response_failed(response: &serde_json::Value) -> bool {
+    match response {
+        serde_json::Value::String(text) => text.contains("<tool_use_error>"),
+        serde_json::Value::Object(obj) => {
+            obj.get("success").and_then(|v| v.as_bool()) == Some(false)
+                || obj.get("is_error").and_then(|v| v.as_bool()) == Some(true)
+                || obj.get("interrupted").and_then(|v| v.as_bool()) == Some(true)
+                || obj.get("error").is_some_and(|e| !e.is_null() && e != false)
+        }
+        _ => false,
+    }
+}
+
+/// The most telling part of a failed call's output: an explicit `error`
+/// field, then stderr, then whatever text the tool returned.
+pub fn error_text(response: Option<&serde_json::Value>, result: Option<&str>) -> String {
+    let from_response = response.and_then(|r| {
+        ["error", "stderr"]
+            .iter()
+            .filter_map(|key| r.get(*key).and_then(|v| v.as_str()))
+            .find(|text| !text.trim().is_empty())
+            .map(|text| text.to_string())
+    });
+    from_response
+        .or_else(|| result.map(|r| r.to_string()))
+        .unwrap_or_default()
+}
+
+/// Trim error output down to a single readable line.
+pub fn excerpt(text: &str) -> String {
+    let text = text
+        .replace("<tool_use_error>", "")
+        .replace("</tool_use_error>", "");
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.is_empty() {
+        return "no error output".to_string();
+    }
+    crate::text::ellipsize(&collapsed, EXCERPT_CHARS)
+}
+
+/// Usage and failure totals for one tool across the diary.
+#[derive(Debug, Clone)]
+pub struct ToolStats {
+    pub tool_name: String,
+    pub uses: u32,
+    pub failures: u32,
+    pub recent_errors: Vec<String>,
+}
+
+impl ToolStats {
+    pub fn failure_rate(&self) -> f64 {
+        if self.uses == 0 {
+            0.0
+        } else {
+            self.failures as f64 / self.uses as f64
+        }
+    }
+}
+
+/// Render the per-tool failure report, most failing tools first.
+pub fn format_report(stats: &[ToolStats]) -> String {
+    let mut content = String::new();
+    let failing: Vec<&ToolStats> = stats.iter().filter(|s| s.failures > 0).collect();
+
+    content.push_str("### ❌ **Tool Failures**\n");
+    if failing.is_empty() {
+        content.push_str("- No tool failures recorded\n");
+    }
+    for tool in &failing {
+        content.push_str(&format!(
+            "- **{}**: {} of {} calls failed ({:.0}%)\n",
+            tool.tool_name,
+            tool.failures,
+            tool.uses,
+            tool.failure_rate() * 100.0
+        ));
+        for error in &tool.recent_errors {
+            content.push_str(&format!("  - {}\n", error));
+        }
+    }
+    content.push('\n');
+
+    let reliable: Vec<&ToolStats> = stats.iter().filter(|s| s.failures == 0).collect();
+    if !reliable.is_empty() {
+        content.push_str("### ✅ **No Failures**\n");
+        for tool in reliable {
+            content.push_str(&format!("- {}: {} calls\n", tool.tool_name, tool.uses));
+        }
+        content.push('\n');
+    }
+    content
+}
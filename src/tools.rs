@@ -0,0 +1,148 @@
+use crate::config::ToolCategoryRule;
+
+/// Categories for tools that ship with Claude Code and for common MCP
+/// servers, as (glob pattern, category). Rules from the config are tried
+/// first; the first matching rule wins.
+const DEFAULT_RULES: &[(&str, &str)] = &[
+    ("Edit", "Code Development"),
+    ("MultiEdit", "Code Development"),
+    ("Write", "Code Development"),
+    ("NotebookEdit", "Code Development"),
+    ("Read", "Code Analysis"),
+    ("NotebookRead", "Code Analysis"),
+    ("Glob", "Code Analysis"),
+    ("LS", "Code Analysis"),
+    ("Grep", "Code Search"),
+    ("Bash", "System Operations"),
+    ("BashOutput", "System Operations"),
+    ("KillShell", "System Operations"),
+    ("Task", "AI Collaboration"),
+    ("TodoWrite", "Project Management"),
+    ("ExitPlanMode", "Planning"),
+    ("WebFetch", "Research"),
+    ("WebSearch", "Research"),
+    ("mcp__github__*", "Version Control"),
+    ("mcp__gitlab__*", "Version Control"),
+    ("mcp__git__*", "Version Control"),
+    ("mcp__postgres*__*", "Database Operations"),
+    ("mcp__sqlite__*", "Database Operations"),
+    ("mcp__mysql__*", "Database Operations"),
+    ("mcp__puppeteer__*", "Browser Automation"),
+    ("mcp__playwright__*", "Browser Automation"),
+    ("mcp__fetch__*", "Research"),
+    ("mcp__brave*__*", "Research"),
+    ("mcp__context7__*", "Research"),
+    ("mcp__filesystem__*", "Code Analysis"),
+    ("mcp__*", "External Tools"),
+];
+
+/// A tool provided by an MCP server, named `mcp__<server>__<tool>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct McpTool<'a> {
+    pub server: &'a str,
+    pub tool: &'a str,
+}
+
+/// Split an MCP tool name into its server and tool, or `None` for built-in tools.
+pub fn parse_mcp(tool_name: &str) -> Option<McpTool<'_>> {
+    let mut parts = tool_name.splitn(3, "__");
+    if parts.next()? != "mcp" {
+        return None;
+    }
+    let server = parts.next().filter(|s| !s.is_empty())?;
+    let tool = parts.next().filter(|t| !t.is_empty())?;
+    Some(McpTool { server, tool })
+}
+
+/// A readable name for a tool: `create_pull_request (github)` for MCP tools,
+/// the tool name itself otherwise.
+pub fn display_name(tool_name: &str) -> String {
+    match parse_mcp(tool_name) {
+        Some(mcp) => format!("{} ({})", mcp.tool, mcp.server),
+        None => tool_name.to_string(),
+    }
+}
+
+/// The accomplishment category for a tool, checking the configured rules
+/// before the built-in ones and falling back to "Other".
+pub fn categorize(tool_name: &str, rules: &[ToolCategoryRule]) -> String {
+    rules
+        .iter()
+        .map(|rule| (rule.pattern.as_str(), rule.category.as_str()))
+        .chain(DEFAULT_RULES.iter().copied())
+        .find(|(pattern, _)| glob_matches(pattern, tool_name))
+        .map(|(_, category)| category.to_string())
+        .unwrap_or_else(|| "Other".to_string())
+}
+
+/// Match a tool name against a glob where `*` matches any run of characters
+/// and `?` matches one character.
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Where the last `*` was seen and how much of the text it had absorbed
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, absorbed)) => {
+                    p = star + 1;
+                    t = absorbed + 1;
+                    backtrack = Some((star, absorbed + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Usage of one MCP server across the diary.
+#[derive(Debug, Clone)]
+pub struct McpServerStats {
+    pub server: String,
+    pub calls: u32,
+    pub failures: u32,
+    pub tools: Vec<(String, u32)>,
+}
+
+/// Render the Markdown "MCP Servers" section of the tool report.
+pub fn format_mcp_section(servers: &[McpServerStats]) -> String {
+    let mut content = String::new();
+    if servers.is_empty() {
+        return content;
+    }
+
+    content.push_str("### 🔌 **MCP Servers**\n");
+    for server in servers {
+        let failed = if server.failures > 0 {
+            format!(", {} failed", server.failures)
+        } else {
+            String::new()
+        };
+        content.push_str(&format!(
+            "- **{}**: {} call{} across {} tool{}{}\n",
+            server.server,
+            server.calls,
+            if server.calls == 1 { "" } else { "s" },
+            server.tools.len(),
+            if server.tools.len() == 1 { "" } else { "s" },
+            failed
+        ));
+        for (tool, calls) in &server.tools {
+            content.push_str(&format!("  - {}: {}\n", tool, calls));
+        }
+    }
+    content.push('\n');
+    content
+}
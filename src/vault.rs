@@ -0,0 +1,108 @@
+//! Daily notes in a Markdown vault (Obsidian, Logseq). The diary is written
+//! inside a delimited block, so the rest of the note stays the user's own.
+
+use crate::config::VaultConfig;
+use crate::output::SessionRecord;
+use crate::{format_active_time, project_name};
+use anyhow::{bail, Context, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::NaiveDate;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const BLOCK_START: &str = "<!-- claude-diary-hook:start -->";
+pub const BLOCK_END: &str = "<!-- claude-diary-hook:end -->";
+
+/// The note for `day`: the configured vault directory joined with the
+/// filename pattern, e.g. `%Y-%m-%d.md` or `journals/%Y_%m_%d.md`.
+pub fn note_path(config: &VaultConfig, vault: Option<&str>, day: NaiveDate) -> Result<PathBuf> {
+    let Some(vault) = vault.or(config.path.as_deref()) else {
+        bail!("No vault configured: pass --vault or set vault.path in diary-config.json");
+    };
+    let vault = match (vault.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(vault),
+    };
+    // chrono panics when formatting with an invalid pattern, so check it first
+    let pattern = &config.filename_pattern;
+    if StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error)) {
+        bail!("Invalid vault.filename_pattern '{}': see the chrono strftime format", pattern);
+    }
+    Ok(vault.join(day.format(pattern).to_string()))
+}
+
+fn heading(level: usize, text: &str) -> String {
+    format!("{} {}\n", "#".repeat(level.clamp(1, 6)), text)
+}
+
+/// The day's diary as Markdown, with its top heading at `heading_level`.
+pub fn format_day(day: NaiveDate, narrative: Option<&str>, sessions: &[SessionRecord], heading_level: usize) -> String {
+    let mut content = heading(heading_level, &format!("Claude diary, {}", day.format("%A %Y-%m-%d")));
+    if let Some(narrative) = narrative {
+        content.push_str(&format!("\n{}\n", narrative));
+    }
+    for session in sessions {
+        let start = crate::dates::local_time(&session.start_time)
+            .map(|time| time.format("%H:%M").to_string())
+            .unwrap_or_else(|| session.start_time.clone());
+        let project = project_name(session.cwd.as_deref()).unwrap_or_else(|| "Other".to_string());
+        content.push('\n');
+        content.push_str(&heading(
+            heading_level + 1,
+            &format!("{} {} ({})", start, project, format_active_time(session.active_ms, session.active_segments)),
+        ));
+        if let Some(narrative) = &session.narrative {
+            content.push_str(&format!("\n{}\n", narrative));
+        }
+        if !session.accomplishments.is_empty() {
+            content.push('\n');
+            for accomplishment in &session.accomplishments {
+                content.push_str(&format!("- **{}**: {}\n", accomplishment.category, accomplishment.description));
+            }
+        }
+        if !session.issues.is_empty() {
+            content.push_str(&format!("\n{}", heading(heading_level + 2, "Issues")));
+            for issue in &session.issues {
+                content.push_str(&format!("- {}\n", issue.issue));
+            }
+        }
+        if !session.files_modified.is_empty() {
+            content.push_str(&format!("\n{}", heading(heading_level + 2, "Files")));
+            for file in &session.files_modified {
+                content.push_str(&format!("- `{}` (+{} -{})\n", file.path, file.lines_added, file.lines_removed));
+            }
+        }
+    }
+    content
+}
+
+/// Put `block` between the markers in `note`, replacing what was there
+/// before and leaving everything outside them untouched. A note without
+/// markers gets the block appended.
+pub fn update_note(note: &str, block: &str) -> String {
+    let managed = format!("{}\n{}\n{}", BLOCK_START, block.trim_end(), BLOCK_END);
+    if let Some(start) = note.find(BLOCK_START) {
+        if let Some(end) = note[start..].find(BLOCK_END) {
+            let end = start + end + BLOCK_END.len();
+            return format!("{}{}{}", &note[..start], managed, &note[end..]);
+        }
+    }
+    if note.trim().is_empty() {
+        return format!("{}\n", managed);
+    }
+    let separator = if note.ends_with("\n\n") { "" } else if note.ends_with('\n') { "\n" } else { "\n\n" };
+    format!("{}{}{}\n", note, separator, managed)
+}
+
+/// Write `block` into the note at `path`, creating it and its directory if needed.
+pub fn write_note(path: &Path, block: &str) -> Result<()> {
+    let note = match fs::read_to_string(path) {
+        Ok(note) => note,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    fs::write(path, update_note(&note, block)).with_context(|| format!("Failed to write {}", path.display()))
+}
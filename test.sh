@@ -4,6 +4,9 @@ set -e
 
 HOOK_BINARY="./target/release/claude-diary-hook"
 
+# Replayed events carry UTC timestamps; read days and times back in UTC too
+export TZ=UTC
+
 if [ ! -f "$HOOK_BINARY" ]; then
     echo "❌ Hook binary not found. Run ./build.sh first"
     exit 1
@@ -125,12 +128,603 @@ else
     echo "❌ Test 7 failed - Could not create test database in old location"
 fi
 
+# Test 8: Test runner output
+echo "📝 Test 8: Test run extraction"
+{
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-8", "tool_name": "Bash", "tool_input": {"command": "cargo test"}, "tool_response": {"stdout": "test auth::login ... FAILED\ntest result: FAILED. 4 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.05s"}}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-8", "tool_name": "Bash", "tool_input": {"command": "cargo test"}, "tool_response": {"stdout": "test result: ok. 5 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.04s"}}'
+} | $HOOK_BINARY --test > /tmp/test8_output.txt
+
+if grep -q "Tests went from 1 failing to green" /tmp/test8_output.txt; then
+    echo "✅ Test 8 passed - Test runs extracted"
+else
+    echo "❌ Test 8 failed - Test run extraction broken"
+    cat /tmp/test8_output.txt
+fi
+
+# Test 9: Date spans too large to count
+echo "📝 Test 9: Out-of-range date spans"
+$HOOK_BINARY --diary-dir "$TEST_DIR" show 99999999999d > /tmp/test9_output.txt 2>&1 || true
+$HOOK_BINARY --diary-dir "$TEST_DIR" show 2000000000000000000w >> /tmp/test9_output.txt 2>&1 || true
+$HOOK_BINARY --diary-dir "$TEST_DIR" show --since 99999999999d >> /tmp/test9_output.txt 2>&1 || true
+
+if [ "$(grep -c "Unrecognised date" /tmp/test9_output.txt)" -eq 3 ] && ! grep -q "panicked" /tmp/test9_output.txt; then
+    echo "✅ Test 9 passed - Oversized date spans are rejected"
+else
+    echo "❌ Test 9 failed - Oversized date spans not rejected"
+    cat /tmp/test9_output.txt
+fi
+
+# Test 10: Invalid vault filename pattern
+echo "📝 Test 10: Invalid vault filename pattern"
+VAULT_TEST_DIR="/tmp/claude-diary-vault-test"
+mkdir -p "$VAULT_TEST_DIR/diary"
+echo '{"vault": {"path": "'"$VAULT_TEST_DIR"'/vault", "filename_pattern": "%Q-%Y.md", "on_session_end": true}}' > "$VAULT_TEST_DIR/diary/diary-config.json"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-10", "cwd": "/tmp/web", "prompt": "update the readme"}'
+  echo '{"hook_event_name": "SessionEnd", "session_id": "test-10", "cwd": "/tmp/web"}'
+} | $HOOK_BINARY --diary-dir "$VAULT_TEST_DIR/diary" > /tmp/test10_output.txt 2>&1 && HOOK_STATUS=0 || HOOK_STATUS=$?
+$HOOK_BINARY --diary-dir "$VAULT_TEST_DIR/diary" export vault >> /tmp/test10_output.txt 2>&1 || true
+
+if [ "$HOOK_STATUS" -eq 0 ] && [ "$(grep -c "Invalid vault.filename_pattern" /tmp/test10_output.txt)" -eq 2 ] && ! grep -q "panicked" /tmp/test10_output.txt; then
+    echo "✅ Test 10 passed - Invalid filename patterns are reported"
+else
+    echo "❌ Test 10 failed - Invalid filename pattern not handled"
+    cat /tmp/test10_output.txt
+fi
+
+# Test 11: Failing test runs exit non-zero but still count as runs
+echo "📝 Test 11: Failed test run extraction"
+{
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-11", "tool_name": "Bash", "tool_input": {"command": "cargo test"}, "tool_response": {"is_error": true, "stdout": "test auth::login ... FAILED\ntest result: FAILED. 4 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.05s", "stderr": "error: test failed"}}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-11", "tool_name": "Bash", "tool_input": {"command": "cargo test"}, "tool_response": {"stdout": "test result: ok. 5 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.04s"}}'
+} | $HOOK_BINARY --test > /tmp/test11_output.txt
+
+if grep -q "Tests went from 1 failing to green" /tmp/test11_output.txt; then
+    echo "✅ Test 11 passed - Failed test runs recorded"
+else
+    echo "❌ Test 11 failed - Failed test runs missing"
+    cat /tmp/test11_output.txt
+fi
+
+# Test 12: A failed tool call is not evidence of progress
+echo "📝 Test 12: Failed tool calls don't confirm requests"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-12", "prompt": "fix the login bug"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-12", "tool_name": "Edit", "tool_input": {"file_path": "/tmp/x/login.rs", "old_string": "a", "new_string": "b"}, "tool_response": {"is_error": true, "error": "String to replace not found"}}'
+  echo '{"hook_event_name": "Stop", "session_id": "test-12"}'
+} | $HOOK_BINARY --test --include-unconfirmed > /tmp/test12_output.txt
+
+if grep -q "fix the login bug\*\* _\[requested\]_" /tmp/test12_output.txt; then
+    echo "✅ Test 12 passed - Failed tool calls leave requests unconfirmed"
+else
+    echo "❌ Test 12 failed - Failed tool call advanced the request"
+    cat /tmp/test12_output.txt
+fi
+
+# Test 13: Timesheet descriptions leave out tool activity
+echo "📝 Test 13: Timesheet descriptions"
+TIMESHEET_TEST_DIR="/tmp/claude-diary-timesheet-test"
+rm -rf "$TIMESHEET_TEST_DIR"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-13", "cwd": "/tmp/billing", "prompt": "fix the login bug", "timestamp": "2026-01-05T12:00:00Z"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-13", "cwd": "/tmp/billing", "tool_name": "Read", "tool_input": {"file_path": "/tmp/billing/login.rs"}, "tool_response": {"success": true}, "timestamp": "2026-01-05T12:05:00Z"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-13", "cwd": "/tmp/billing", "tool_name": "Edit", "tool_input": {"file_path": "/tmp/billing/login.rs", "old_string": "a", "new_string": "b"}, "tool_response": {"success": true}, "timestamp": "2026-01-05T12:10:00Z"}'
+  echo '{"hook_event_name": "Stop", "session_id": "test-13", "cwd": "/tmp/billing", "timestamp": "2026-01-05T12:20:00Z"}'
+} | $HOOK_BINARY --diary-dir "$TIMESHEET_TEST_DIR" > /dev/null
+$HOOK_BINARY --diary-dir "$TIMESHEET_TEST_DIR" timesheet 2026-01-05 > /tmp/test13_output.txt
+
+if grep -q "^2026-01-05,billing,,,.*fix the login bug" /tmp/test13_output.txt && ! grep -qE "Read |Modified " /tmp/test13_output.txt; then
+    echo "✅ Test 13 passed - Timesheet descriptions come from requests, todos and replies"
+else
+    echo "❌ Test 13 failed - Timesheet description broken"
+    cat /tmp/test13_output.txt
+fi
+
+# Test 14: Date range queries
+echo "📝 Test 14: Date range queries"
+RANGE_TEST_DIR="/tmp/claude-diary-range-test"
+rm -rf "$RANGE_TEST_DIR"
+echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-14a", "cwd": "/tmp/range-alpha", "prompt": "add the parser", "timestamp": "2026-01-05T12:00:00Z"}' | $HOOK_BINARY --diary-dir "$RANGE_TEST_DIR" > /dev/null
+echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-14b", "cwd": "/tmp/range-beta", "prompt": "add the lexer", "timestamp": "2026-01-07T12:00:00Z"}' | $HOOK_BINARY --diary-dir "$RANGE_TEST_DIR" > /dev/null
+$HOOK_BINARY --diary-dir "$RANGE_TEST_DIR" show --since 2026-01-06 --until 2026-01-08 > /tmp/test14_range.txt
+$HOOK_BINARY --diary-dir "$RANGE_TEST_DIR" show 2026-01-05 > /tmp/test14_day.txt
+
+if grep -q "range-beta" /tmp/test14_range.txt && ! grep -q "range-alpha" /tmp/test14_range.txt \
+    && grep -q "range-alpha" /tmp/test14_day.txt && ! grep -q "range-beta" /tmp/test14_day.txt; then
+    echo "✅ Test 14 passed - Date ranges select the right sessions"
+else
+    echo "❌ Test 14 failed - Date range queries broken"
+    cat /tmp/test14_range.txt /tmp/test14_day.txt
+fi
+
+# Test 15: Line churn
+echo "📝 Test 15: Line churn counting"
+{
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-15", "cwd": "/tmp/churn", "tool_name": "Write", "tool_input": {"file_path": "/tmp/churn/a.rs", "content": "one\ntwo\nthree\n"}, "tool_response": {"success": true}}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-15", "cwd": "/tmp/churn", "tool_name": "Edit", "tool_input": {"file_path": "/tmp/churn/a.rs", "old_string": "two", "new_string": "two\ntwo and a half"}, "tool_response": {"success": true}}'
+} | $HOOK_BINARY --test > /tmp/test15_output.txt
+
+if grep -q "/tmp/churn/a.rs _(+4/-0)_" /tmp/test15_output.txt; then
+    echo "✅ Test 15 passed - Line churn counted"
+else
+    echo "❌ Test 15 failed - Line churn counting broken"
+    cat /tmp/test15_output.txt
+fi
+
+# Test 16: Calendar export
+echo "📝 Test 16: iCalendar escaping and line folding"
+ICS_TEST_DIR="/tmp/claude-diary-ics-test"
+rm -rf "$ICS_TEST_DIR"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-16", "cwd": "/tmp/calendar", "prompt": "fix the parser, the lexer; and the tokenizer so that long descriptions wrap across several calendar lines", "timestamp": "2026-01-05T12:00:00Z"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-16", "cwd": "/tmp/calendar", "tool_name": "Edit", "tool_input": {"file_path": "/tmp/calendar/parser.rs", "old_string": "a", "new_string": "b"}, "tool_response": {"success": true}, "timestamp": "2026-01-05T12:05:00Z"}'
+  echo '{"hook_event_name": "Stop", "session_id": "test-16", "cwd": "/tmp/calendar", "timestamp": "2026-01-05T12:06:00Z"}'
+} | $HOOK_BINARY --diary-dir "$ICS_TEST_DIR" > /dev/null
+$HOOK_BINARY --diary-dir "$ICS_TEST_DIR" export --format ics > /tmp/test16_output.txt
+$HOOK_BINARY --diary-dir "$ICS_TEST_DIR" --format ics export 2026-01-05 > /tmp/test16_again.txt
+UNFOLDED=$(tr -d '\r' < /tmp/test16_output.txt | sed -e ':a' -e 'N' -e '$!ba' -e 's/\n //g')
+
+if echo "$UNFOLDED" | grep -q 'fix the parser\\, the lexer\\; and the tokenizer' \
+    && echo "$UNFOLDED" | grep -q "ATTACH:file:///tmp/calendar/parser.rs" \
+    && LC_ALL=C awk '{ if (length($0) > 76 || substr($0, length($0)) != "\r") bad = 1 } END { exit bad }' /tmp/test16_output.txt \
+    && grep -q "^ " /tmp/test16_output.txt \
+    && diff <(grep UID /tmp/test16_output.txt) <(grep UID /tmp/test16_again.txt) > /dev/null; then
+    echo "✅ Test 16 passed - Calendar events escaped, folded and stable"
+else
+    echo "❌ Test 16 failed - Calendar export broken"
+    cat /tmp/test16_output.txt
+fi
+
+# Test 17: Vault notes keep what is outside the managed block
+echo "📝 Test 17: Vault note managed block"
+mkdir -p "$VAULT_TEST_DIR/notes"
+printf '# Monday\n\nMeeting notes\n\n<!-- claude-diary-hook:start -->\nstale diary\n<!-- claude-diary-hook:end -->\n\nTodo: call back\n' > "$VAULT_TEST_DIR/notes/2026-01-05.md"
+$HOOK_BINARY --diary-dir "$ICS_TEST_DIR" export vault 2026-01-05 --vault "$VAULT_TEST_DIR/notes" > /dev/null
+$HOOK_BINARY --diary-dir "$ICS_TEST_DIR" export vault 2026-01-05 --vault "$VAULT_TEST_DIR/notes" > /dev/null
+cp "$VAULT_TEST_DIR/notes/2026-01-05.md" /tmp/test17_output.txt
+
+if grep -q "^Meeting notes" /tmp/test17_output.txt && grep -q "^Todo: call back" /tmp/test17_output.txt \
+    && ! grep -q "stale diary" /tmp/test17_output.txt && grep -q "^## Claude diary" /tmp/test17_output.txt \
+    && [ "$(grep -c "claude-diary-hook:start" /tmp/test17_output.txt)" -eq 1 ]; then
+    echo "✅ Test 17 passed - Managed block replaced, the rest kept"
+else
+    echo "❌ Test 17 failed - Vault note update broken"
+    cat /tmp/test17_output.txt
+fi
+
+# Test 18: Timesheet rounding (the Test 13 session is 20 active minutes)
+echo "📝 Test 18: Timesheet rounding"
+$HOOK_BINARY --diary-dir "$TIMESHEET_TEST_DIR" timesheet 2026-01-05 > /tmp/test18_nearest.txt
+echo '{"timesheet": {"increment_minutes": 30, "rounding": "up"}}' > "$TIMESHEET_TEST_DIR/diary-config.json"
+$HOOK_BINARY --diary-dir "$TIMESHEET_TEST_DIR" timesheet 2026-01-05 > /tmp/test18_up.txt
+echo '{"timesheet": {"increment_minutes": 30, "rounding": "down"}}' > "$TIMESHEET_TEST_DIR/diary-config.json"
+$HOOK_BINARY --diary-dir "$TIMESHEET_TEST_DIR" timesheet 2026-01-05 > /tmp/test18_down.txt
+
+if grep -q "^2026-01-05,billing,,,0.25," /tmp/test18_nearest.txt && grep -q "^2026-01-05,billing,,,0.50," /tmp/test18_up.txt \
+    && [ "$(wc -l < /tmp/test18_down.txt)" -eq 1 ]; then
+    echo "✅ Test 18 passed - Timesheet hours rounded"
+else
+    echo "❌ Test 18 failed - Timesheet rounding broken"
+    cat /tmp/test18_nearest.txt /tmp/test18_up.txt /tmp/test18_down.txt
+fi
+
+# Test 19: Replayed events are recorded on the day they happened
+echo "📝 Test 19: Past-dated events"
+PAST_TEST_DIR="/tmp/claude-diary-past-test"
+rm -rf "$PAST_TEST_DIR"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-19", "cwd": "/tmp/past", "prompt": "fix the login bug in auth.rs", "timestamp": "2026-01-07T12:00:00Z"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-19", "cwd": "/tmp/past", "tool_name": "Edit", "tool_input": {"file_path": "/tmp/past/auth.rs", "old_string": "a", "new_string": "b"}, "tool_response": {"success": true}, "timestamp": "2026-01-07T12:05:00Z"}'
+  echo '{"event_type": "error", "session_id": "test-19", "cwd": "/tmp/past", "error": "build failed", "timestamp": "2026-01-07T12:06:00Z"}'
+  echo '{"hook_event_name": "Stop", "session_id": "test-19", "cwd": "/tmp/past", "timestamp": "2026-01-07T12:10:00Z"}'
+} | $HOOK_BINARY --diary-dir "$PAST_TEST_DIR" > /dev/null
+$HOOK_BINARY --diary-dir "$PAST_TEST_DIR" show 2026-01-07 --include-unconfirmed > /tmp/test19_output.txt
+$HOOK_BINARY --diary-dir "$PAST_TEST_DIR" show > /tmp/test19_today.txt
+
+if grep -q "fix the login bug in auth.rs\*\*" /tmp/test19_output.txt && grep -q "Modified /tmp/past/auth.rs" /tmp/test19_output.txt \
+    && grep -q "Error encountered: build failed" /tmp/test19_output.txt && grep -q "No sessions in this period" /tmp/test19_today.txt; then
+    echo "✅ Test 19 passed - Past-dated events show on their own day"
+else
+    echo "❌ Test 19 failed - Past-dated events recorded on the wrong day"
+    cat /tmp/test19_output.txt /tmp/test19_today.txt
+fi
+
+# Test 20: Active time and end time come from the events, whatever their durations
+echo "📝 Test 20: Idle-aware active time"
+IDLE_TEST_DIR="/tmp/claude-diary-idle-test"
+rm -rf "$IDLE_TEST_DIR"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-20", "cwd": "/tmp/idle", "prompt": "fix the login bug", "timestamp": "2026-01-07T12:00:00Z"}'
+  echo '{"event_type": "tool_call", "session_id": "test-20", "cwd": "/tmp/idle", "duration_ms": 10000000000000000, "tool_calls": [{"tool_name": "Read", "parameters": {"file_path": "/tmp/idle/login.rs"}, "success": true}], "timestamp": "2026-01-07T12:05:00Z"}'
+  echo '{"hook_event_name": "Stop", "session_id": "test-20", "cwd": "/tmp/idle", "timestamp": "2026-01-07T12:08:00Z"}'
+  echo '{"hook_event_name": "SessionEnd", "session_id": "test-20", "cwd": "/tmp/idle", "timestamp": "2026-01-07T15:00:00Z"}'
+} | $HOOK_BINARY --diary-dir "$IDLE_TEST_DIR" > /tmp/test20_output.txt 2>&1
+$HOOK_BINARY --diary-dir "$IDLE_TEST_DIR" show 2026-01-07 >> /tmp/test20_output.txt
+$HOOK_BINARY --diary-dir "$IDLE_TEST_DIR" --format json show 2026-01-07 >> /tmp/test20_output.txt
+
+if grep -q "~8 minutes active in 2 segments" /tmp/test20_output.txt && grep -q '"end_time": "2026-01-07T15:00:00' /tmp/test20_output.txt \
+    && ! grep -q "panicked" /tmp/test20_output.txt; then
+    echo "✅ Test 20 passed - Active time split at idle gaps"
+else
+    echo "❌ Test 20 failed - Active time computation broken"
+    cat /tmp/test20_output.txt
+fi
+
+# Test 21: Only commands that run tests are test runs
+echo "📝 Test 21: Test runs come from test commands"
+{
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-21", "tool_name": "Read", "tool_input": {"file_path": "/tmp/ci/last-run.log"}, "tool_response": {"content": "test result: ok. 5 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out"}}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-21", "tool_name": "Bash", "tool_input": {"command": "cat /tmp/ci/last-run.log"}, "tool_response": {"stdout": "test result: ok. 5 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out"}}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-21", "tool_name": "Bash", "tool_input": {"command": "npm test"}, "tool_response": {"stdout": "Test Suites: 1 failed, 1 total\nTests:       1 failed, 2 errors, 3 passed, 6 total\nTime:        1.2 s"}}'
+} | $HOOK_BINARY --test > /tmp/test21_output.txt
+
+if grep -q "_Tests failing (1 failing)_" /tmp/test21_output.txt && grep -q "jest: 3 passed, 1 failed, 0 skipped" /tmp/test21_output.txt \
+    && ! grep -q "cargo:" /tmp/test21_output.txt; then
+    echo "✅ Test 21 passed - Only test commands are recorded as test runs"
+else
+    echo "❌ Test 21 failed - Test-like output recorded as a test run"
+    cat /tmp/test21_output.txt
+fi
+
+# Test 22: Reading test output is not a passing test run
+echo "📝 Test 22: Test-like file contents don't confirm requests"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-22", "prompt": "fix the login bug"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-22", "tool_name": "Read", "tool_input": {"file_path": "/tmp/ci/last-run.log"}, "tool_response": {"content": "test result: ok. 5 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out"}}'
+} | $HOOK_BINARY --test --include-unconfirmed > /tmp/test22_output.txt
+
+if grep -q "fix the login bug\*\* _\[in progress\]_" /tmp/test22_output.txt && ! grep -q "Test Runs" /tmp/test22_output.txt; then
+    echo "✅ Test 22 passed - Reading test output leaves requests in progress"
+else
+    echo "❌ Test 22 failed - Reading test output confirmed a request"
+    cat /tmp/test22_output.txt
+fi
+
+# Test 23: Commits made during a session are recorded
+echo "📝 Test 23: Git commits during a session"
+GIT_TEST_DIR="/tmp/claude-diary-git-test"
+rm -rf "$GIT_TEST_DIR"
+mkdir -p "$GIT_TEST_DIR/repo"
+git -C "$GIT_TEST_DIR/repo" init -q -b main
+git -C "$GIT_TEST_DIR/repo" -c user.name=Test -c user.email=test@example.com commit -q --allow-empty -m "Initial commit"
+echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-23", "cwd": "'"$GIT_TEST_DIR"'/repo", "prompt": "add a login page"}' | $HOOK_BINARY --diary-dir "$GIT_TEST_DIR/diary" > /dev/null
+printf 'one\ntwo\n' > "$GIT_TEST_DIR/repo/login.html"
+git -C "$GIT_TEST_DIR/repo" add login.html
+git -C "$GIT_TEST_DIR/repo" -c user.name=Test -c user.email=test@example.com commit -q -m "Add the login page"
+echo '{"hook_event_name": "Stop", "session_id": "test-23", "cwd": "'"$GIT_TEST_DIR"'/repo"}' | $HOOK_BINARY --diary-dir "$GIT_TEST_DIR/diary" > /dev/null
+$HOOK_BINARY --diary-dir "$GIT_TEST_DIR/diary" show > /tmp/test23_output.txt
+
+if grep -q "Branch: main @" /tmp/test23_output.txt && grep -q "Add the login page _(1 file, +2/-0)_" /tmp/test23_output.txt \
+    && ! grep -q "Initial commit" /tmp/test23_output.txt; then
+    echo "✅ Test 23 passed - Commits made during the session recorded"
+else
+    echo "❌ Test 23 failed - Git activity not recorded"
+    cat /tmp/test23_output.txt
+fi
+
+# Test 24: The standup plans what was asked for but never done
+echo "📝 Test 24: Standup open objectives"
+STANDUP_TEST_DIR="/tmp/claude-diary-standup-test"
+rm -rf "$STANDUP_TEST_DIR"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-24", "cwd": "/tmp/shop", "prompt": "fix the login bug in auth.rs", "timestamp": "2026-01-09T12:00:00Z"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-24", "cwd": "/tmp/shop", "tool_name": "Edit", "tool_input": {"file_path": "/tmp/shop/auth.rs", "old_string": "a", "new_string": "b"}, "tool_response": {"success": true}, "timestamp": "2026-01-09T12:05:00Z"}'
+  echo '{"hook_event_name": "Stop", "session_id": "test-24", "cwd": "/tmp/shop", "timestamp": "2026-01-09T12:10:00Z"}'
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-24", "cwd": "/tmp/shop", "prompt": "fix tests", "timestamp": "2026-01-09T12:20:00Z"}'
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-24", "cwd": "/tmp/shop", "prompt": "ok", "timestamp": "2026-01-09T12:30:00Z"}'
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-24", "cwd": "/tmp/shop", "prompt": "add caching to the product list", "timestamp": "2026-01-09T12:40:00Z"}'
+} | $HOOK_BINARY --diary-dir "$STANDUP_TEST_DIR" > /dev/null
+$HOOK_BINARY --diary-dir "$STANDUP_TEST_DIR" standup --date 2026-01-12 --plain > /tmp/test24_output.txt
+TODAY_SECTION=$(sed -n '/^Today:/,/^Blockers:/p' /tmp/test24_output.txt)
+
+if echo "$TODAY_SECTION" | grep -q "  - fix tests" && [ "$(echo "$TODAY_SECTION" | grep -c "add caching to the product list")" -eq 1 ] \
+    && ! echo "$TODAY_SECTION" | grep -qE "  - ok$|login bug|Fixed code issues"; then
+    echo "✅ Test 24 passed - Open objectives planned for today"
+else
+    echo "❌ Test 24 failed - Standup plan wrong"
+    cat /tmp/test24_output.txt
+fi
+
+# Test 25: Tickets mentioned in prompts or encoded in branch names
+echo "📝 Test 25: Ticket references"
+TICKET_TEST_DIR="/tmp/claude-diary-ticket-test"
+rm -rf "$TICKET_TEST_DIR"
+mkdir -p "$TICKET_TEST_DIR/repo"
+git -C "$TICKET_TEST_DIR/repo" init -q -b feature/PROJ-77-login
+git -C "$TICKET_TEST_DIR/repo" -c user.name=Test -c user.email=test@example.com commit -q --allow-empty -m "Initial commit"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-25", "cwd": "'"$TICKET_TEST_DIR"'/repo", "prompt": "fix the PROJ-1234 login crash, see https://github.com/acme/shop/issues/512"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-25", "cwd": "'"$TICKET_TEST_DIR"'/repo", "tool_name": "Edit", "tool_input": {"file_path": "'"$TICKET_TEST_DIR"'/repo/auth.rs", "old_string": "a", "new_string": "b"}, "tool_response": {"success": true}}'
+  echo '{"hook_event_name": "Stop", "session_id": "test-25", "cwd": "'"$TICKET_TEST_DIR"'/repo"}'
+} | $HOOK_BINARY --diary-dir "$TICKET_TEST_DIR/diary" > /dev/null
+for key in proj-1234 "#512" PROJ-77 PROJ-9; do
+  $HOOK_BINARY --diary-dir "$TICKET_TEST_DIR/diary" ticket "$key"
+done > /tmp/test25_output.txt
+
+if [ "$(grep -c "across 1 session" /tmp/test25_output.txt)" -eq 3 ] && [ "$(grep -c "login crash" /tmp/test25_output.txt)" -eq 2 ] \
+    && grep -q "No sessions reference PROJ-9" /tmp/test25_output.txt; then
+    echo "✅ Test 25 passed - Tickets found from prompts, issue URLs and branches"
+else
+    echo "❌ Test 25 failed - Ticket references missing"
+    cat /tmp/test25_output.txt
+fi
+
+# Test 26: Completed todos are the accomplishments
+echo "📝 Test 26: TodoWrite task lists"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-26", "prompt": "add caching to the product list"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-26", "tool_name": "TodoWrite", "tool_input": {"todos": [{"content": "Add a cache layer", "status": "in_progress", "activeForm": "Adding a cache layer"}, {"content": "Invalidate on update", "status": "pending", "activeForm": "Invalidating on update"}]}, "tool_response": {}}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-26", "tool_name": "TodoWrite", "tool_input": {"todos": [{"content": "Add a cache layer", "status": "completed", "activeForm": "Adding a cache layer"}, {"content": "Invalidate on update", "status": "in_progress", "activeForm": "Invalidating on update"}]}, "tool_response": {}}'
+  echo '{"hook_event_name": "Stop", "session_id": "test-26"}'
+} | $HOOK_BINARY --test > /tmp/test26_output.txt
+
+if grep -q -- "- \*\*Add a cache layer\*\*" /tmp/test26_output.txt && grep -q "_(1/2 completed)_" /tmp/test26_output.txt \
+    && grep -q "🔄 Invalidate on update" /tmp/test26_output.txt && ! grep -q "product list\*\*" /tmp/test26_output.txt; then
+    echo "✅ Test 26 passed - Completed todos replace guessed accomplishments"
+else
+    echo "❌ Test 26 failed - Todos not captured"
+    cat /tmp/test26_output.txt
+fi
+
+# Test 27: File references in prompts, resolved against the session directory
+echo "📝 Test 27: File references from prompts"
+FILEREF_TEST_DIR="/tmp/claude-diary-fileref-test"
+rm -rf "$FILEREF_TEST_DIR"
+mkdir -p "$FILEREF_TEST_DIR/src"
+touch "$FILEREF_TEST_DIR/src/App.tsx" "$FILEREF_TEST_DIR/Dockerfile" "$FILEREF_TEST_DIR/my notes.md"
+echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-27", "cwd": "'"$FILEREF_TEST_DIR"'", "prompt": "update `src/App.tsx` and @Dockerfile, compare with \"my notes.md\" and lib/missing.cpp"}' \
+  | $HOOK_BINARY --test --include-unconfirmed > /tmp/test27_output.txt
+
+if grep -q "Files: src/App.tsx, my notes.md, Dockerfile, lib/missing.cpp (not found)$" /tmp/test27_output.txt; then
+    echo "✅ Test 27 passed - Quoted, @-mentioned and extensionless files referenced"
+else
+    echo "❌ Test 27 failed - File references missed"
+    cat /tmp/test27_output.txt
+fi
+
+# Test 28: Reads and searches are explored files, not modified ones
+echo "📝 Test 28: Files explored vs modified"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-28", "prompt": "fix the login bug"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-28", "tool_name": "Read", "tool_input": {"file_path": "/p/src/login.rs"}, "tool_response": {}}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-28", "tool_name": "Grep", "tool_input": {"pattern": "token", "path": "/p/src"}, "tool_response": {}}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-28", "tool_name": "NotebookEdit", "tool_input": {"notebook_path": "/p/notes.ipynb", "new_source": "x"}, "tool_response": {}}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-28", "tool_name": "Edit", "tool_input": {"file_path": "/p/src/auth.rs", "old_string": "a", "new_string": "b"}, "tool_response": {}}'
+  echo '{"hook_event_name": "Stop", "session_id": "test-28"}'
+} | $HOOK_BINARY --test > /tmp/test28_output.txt
+MODIFIED_SECTION=$(sed -n '/Files Modified/,/Files Explored/p' /tmp/test28_output.txt)
+EXPLORED_SECTION=$(sed -n '/Files Explored/,$p' /tmp/test28_output.txt)
+
+if echo "$MODIFIED_SECTION" | grep -q "/p/src/auth.rs" && echo "$MODIFIED_SECTION" | grep -q "/p/notes.ipynb" \
+    && ! echo "$MODIFIED_SECTION" | grep -q "login.rs" && echo "$EXPLORED_SECTION" | grep -q "/p/src/login.rs _(read)_" \
+    && echo "$EXPLORED_SECTION" | grep -q "/p/src _(searched)_"; then
+    echo "✅ Test 28 passed - Only changed files listed as modified"
+else
+    echo "❌ Test 28 failed - Read files listed as modified"
+    cat /tmp/test28_output.txt
+fi
+
+# Test 29: MCP tools are split into server and tool and categorized
+echo "📝 Test 29: MCP tool categories"
+MCP_TEST_DIR="/tmp/claude-diary-mcp-test"
+rm -rf "$MCP_TEST_DIR"
+mkdir -p "$MCP_TEST_DIR"
+echo '{"tool_categories": [{"pattern": "mcp__linear__*", "category": "Project Management"}]}' > "$MCP_TEST_DIR/diary-config.json"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-29", "prompt": "open a pull request for the login fix"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-29", "tool_name": "mcp__github__create_pull_request", "tool_input": {"title": "Fix login"}, "tool_response": {}}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-29", "tool_name": "mcp__github__get_issue", "tool_input": {"issue_number": 512}, "tool_response": {}}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-29", "tool_name": "mcp__linear__create_issue", "tool_input": {"title": "Follow up"}, "tool_response": {}}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-29", "tool_name": "mcp__acme__deploy", "tool_input": {}, "tool_response": {}}'
+  echo '{"hook_event_name": "Stop", "session_id": "test-29"}'
+} | $HOOK_BINARY --diary-dir "$MCP_TEST_DIR" > /dev/null
+$HOOK_BINARY --diary-dir "$MCP_TEST_DIR" tools > /tmp/test29_output.txt
+$HOOK_BINARY --diary-dir "$MCP_TEST_DIR" show >> /tmp/test29_output.txt
+
+if grep -q "\*\*github\*\*: 2 calls across 2 tools" /tmp/test29_output.txt && grep -A1 "#### \*\*Project Management\*\*" /tmp/test29_output.txt | grep -q "create_issue (linear)" \
+    && grep -A1 "#### \*\*Version Control\*\*" /tmp/test29_output.txt | grep -q "(github)" && grep -A1 "#### \*\*External Tools\*\*" /tmp/test29_output.txt | grep -q "deploy (acme)"; then
+    echo "✅ Test 29 passed - MCP tools grouped by server and categorized"
+else
+    echo "❌ Test 29 failed - MCP tools not categorized"
+    cat /tmp/test29_output.txt
+fi
+
+# Test 30: Technologies come from files, manifests and commands
+echo "📝 Test 30: Tech stack per session"
+TECH_TEST_DIR="/tmp/claude-diary-tech-test"
+rm -rf "$TECH_TEST_DIR"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-30a", "prompt": "add an endpoint for orders", "timestamp": "2026-01-07T12:00:00Z"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-30a", "tool_name": "Edit", "tool_input": {"file_path": "/p/Cargo.toml", "old_string": "[dependencies]", "new_string": "[dependencies]\naxum = \"0.7\""}, "tool_response": {}, "timestamp": "2026-01-07T12:05:00Z"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-30a", "tool_name": "Bash", "tool_input": {"command": "terraform plan"}, "tool_response": {"stdout": "No changes."}, "timestamp": "2026-01-07T12:10:00Z"}'
+  echo '{"hook_event_name": "SessionEnd", "session_id": "test-30a", "timestamp": "2026-01-07T12:10:00Z"}'
+} | $HOOK_BINARY --diary-dir "$TECH_TEST_DIR" > /dev/null
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-30b", "prompt": "restyle the order list", "timestamp": "2026-02-02T12:00:00Z"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-30b", "tool_name": "Edit", "tool_input": {"file_path": "/p/web/OrderList.tsx", "old_string": "a", "new_string": "b"}, "tool_response": {}, "timestamp": "2026-02-02T12:05:00Z"}'
+  echo '{"hook_event_name": "SessionEnd", "session_id": "test-30b", "timestamp": "2026-02-02T12:05:00Z"}'
+} | $HOOK_BINARY --diary-dir "$TECH_TEST_DIR" > /dev/null
+$HOOK_BINARY --diary-dir "$TECH_TEST_DIR" tech --since 2026-01-01 --until 2026-01-31 > /tmp/test30_output.txt
+
+if grep -q "\*\*Rust\*\* _(language)_: ~10 minutes across 1 session" /tmp/test30_output.txt && grep -q "\*\*Axum\*\* _(framework)_" /tmp/test30_output.txt \
+    && grep -q "\*\*Terraform\*\*" /tmp/test30_output.txt && ! grep -q "TypeScript" /tmp/test30_output.txt; then
+    echo "✅ Test 30 passed - Technologies detected and timed over the range"
+else
+    echo "❌ Test 30 failed - Tech stack not detected"
+    cat /tmp/test30_output.txt
+fi
+
+# Test 31: Replies are summarized from the transcript, without code
+echo "📝 Test 31: Reply summaries"
+cat > /tmp/test31_transcript.jsonl << 'TRANSCRIPT'
+{"type": "user", "message": {"role": "user", "content": "why does login fail?"}}
+{"type": "assistant", "message": {"role": "assistant", "content": [{"type": "text", "text": "Let me look at the session handling."}]}}
+{"type": "assistant", "message": {"role": "assistant", "content": [{"type": "text", "text": "## Root cause\n\nThe login fails because the session cookie is set without the Secure flag, so the browser drops it on HTTPS redirects.\n\n```rust\ncookie.set_secure(true);\n```\n\nI changed the cookie builder to always set the Secure flag."}]}}
+TRANSCRIPT
+echo '{"hook_event_name": "Stop", "session_id": "test-31", "transcript_path": "/tmp/test31_transcript.jsonl"}' | $HOOK_BINARY --test > /tmp/test31_output.txt
+
+if grep -q "the session cookie is set without the Secure flag" /tmp/test31_output.txt && ! grep -q "set_secure" /tmp/test31_output.txt \
+    && ! grep -q "Analysis and response provided" /tmp/test31_output.txt; then
+    echo "✅ Test 31 passed - Replies summarized from the transcript"
+else
+    echo "❌ Test 31 failed - Reply summary missing"
+    cat /tmp/test31_output.txt
+fi
+
+# Test 32: Sessions and days open with a narrative
+echo "📝 Test 32: Session and daily narratives"
+NARRATIVE_TEST_DIR="/tmp/claude-diary-narrative-test"
+rm -rf "$NARRATIVE_TEST_DIR"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-32a", "cwd": "/tmp/shop", "prompt": "fix the login bug in auth.rs", "timestamp": "2026-01-07T12:00:00Z"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-32a", "cwd": "/tmp/shop", "tool_name": "Edit", "tool_input": {"file_path": "/tmp/shop/auth.rs", "old_string": "a", "new_string": "b"}, "tool_response": {}, "timestamp": "2026-01-07T12:05:00Z"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-32a", "cwd": "/tmp/shop", "tool_name": "Bash", "tool_input": {"command": "cargo test"}, "tool_response": {"stdout": "test result: FAILED. 4 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out"}, "timestamp": "2026-01-07T12:06:00Z"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-32a", "cwd": "/tmp/shop", "tool_name": "Bash", "tool_input": {"command": "cargo test"}, "tool_response": {"stdout": "test result: ok. 5 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out"}, "timestamp": "2026-01-07T12:08:00Z"}'
+  echo '{"event_type": "error", "session_id": "test-32a", "cwd": "/tmp/shop", "error": "clippy warning", "timestamp": "2026-01-07T12:09:00Z"}'
+  echo '{"hook_event_name": "SessionEnd", "session_id": "test-32a", "cwd": "/tmp/shop", "timestamp": "2026-01-07T12:10:00Z"}'
+} | $HOOK_BINARY --diary-dir "$NARRATIVE_TEST_DIR" > /dev/null
+echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-32b", "cwd": "/tmp/blog", "prompt": "write a post about caching", "timestamp": "2026-01-07T15:00:00Z"}' \
+  | $HOOK_BINARY --diary-dir "$NARRATIVE_TEST_DIR" > /dev/null
+$HOOK_BINARY --diary-dir "$NARRATIVE_TEST_DIR" show 2026-01-07 > /tmp/test32_output.txt
+
+if grep -q "^Two sessions in shop and blog (~10 minutes): edited one file, ran tests twice, ending green; one unresolved error.$" /tmp/test32_output.txt \
+    && grep -q "^Worked in shop on fix the login bug in auth.rs; edited one file, ran tests twice, the final run passed; one unresolved error.$" /tmp/test32_output.txt; then
+    echo "✅ Test 32 passed - Narratives summarize sessions and the day"
+else
+    echo "❌ Test 32 failed - Narrative missing or wrong"
+    cat /tmp/test32_output.txt
+fi
+
+# Test 33: Pasted code and backtraces are not objectives; slash commands are their own events
+echo "📝 Test 33: Prompt cleaning and slash commands"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-33", "prompt": "why does this crash?\n```\nfn main() { panic!(\"boom\") }\n```\nthread '"'"'main'"'"' panicked at src/main.rs:1:13:\nnote: run with `RUST_BACKTRACE=1` environment variable to display a backtrace\n   0: rust_begin_unwind\n   1: core::panicking::panic_fmt\n   2: main"}'
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-33", "prompt": "/review the auth module"}'
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-33", "prompt": "<command-name>/compact</command-name>"}'
+} | $HOOK_BINARY --test > /tmp/test33_output.txt
+OBJECTIVES_SECTION=$(sed -n '/Session Objectives/,/^$/p' /tmp/test33_output.txt)
+
+if echo "$OBJECTIVES_SECTION" | grep -q "^- why does this crash?$" && [ "$(echo "$OBJECTIVES_SECTION" | grep -c "^- ")" -eq 1 ] \
+    && grep -q "^- /review$" /tmp/test33_output.txt && grep -q "^- /compact$" /tmp/test33_output.txt; then
+    echo "✅ Test 33 passed - Objectives cleaned and slash commands recorded"
+else
+    echo "❌ Test 33 failed - Pasted material or slash commands became objectives"
+    cat /tmp/test33_output.txt
+fi
+
+# Test 34: Non-English prompts are classified and truncated on character boundaries
+echo "📝 Test 34: Keyword packs and Unicode prompts"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-34", "prompt": "corrige el error de inicio de sesión en el módulo de autenticación, que falla cuando la contraseña contiene acentos como ñ o é"}'
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-34", "prompt": "behebe den Fehler beim Anmelden"}'
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-34", "prompt": "ログイン画面のバグを修正してください。パスワードに全角文字が含まれているとエラーになります。セッションの処理も確認してください。ログアウト後にもう一度ログインした場合も同じエラーが出るかどうか確認してください。"}'
+} | $HOOK_BINARY --test --include-unconfirmed > /tmp/test34_output.txt 2>&1
+
+if [ "$(grep -c "Fixed code issues: " /tmp/test34_output.txt)" -eq 3 ] && grep -q "Fixed code issues: behebe den Fehler beim Anmelden" /tmp/test34_output.txt \
+    && grep -q "Fixed code issues: ログイン画面のバグを修正" /tmp/test34_output.txt && ! grep -q "panicked" /tmp/test34_output.txt; then
+    echo "✅ Test 34 passed - Spanish, German and Japanese prompts classified"
+else
+    echo "❌ Test 34 failed - Non-English prompt misclassified or cut mid-character"
+    cat /tmp/test34_output.txt
+fi
+
+# Test 35: Read commands print versioned JSON and JSON Lines
+echo "📝 Test 35: JSON and JSON Lines output"
+JSON_TEST_DIR="/tmp/claude-diary-json-test"
+rm -rf "$JSON_TEST_DIR"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-35", "cwd": "/tmp/shop", "prompt": "fix the login bug in auth.rs", "timestamp": "2026-01-07T12:00:00Z"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-35", "cwd": "/tmp/shop", "tool_name": "Edit", "tool_input": {"file_path": "/tmp/shop/auth.rs", "old_string": "a", "new_string": "b"}, "tool_response": {}, "timestamp": "2026-01-07T12:05:00Z"}'
+  echo '{"hook_event_name": "Stop", "session_id": "test-35", "cwd": "/tmp/shop", "timestamp": "2026-01-07T12:10:00Z"}'
+} | $HOOK_BINARY --diary-dir "$JSON_TEST_DIR" > /dev/null
+$HOOK_BINARY --diary-dir "$JSON_TEST_DIR" --format json show 2026-01-07 > /tmp/test35_output.txt
+$HOOK_BINARY --diary-dir "$JSON_TEST_DIR" --format jsonl show 2026-01-07 > /tmp/test35_lines.txt
+
+if head -3 /tmp/test35_output.txt | grep -q '"schema_version": 1' && grep -q '"kind": "sessions"' /tmp/test35_output.txt \
+    && grep -q '"claude_session_id": "test-35"' /tmp/test35_output.txt && [ "$(wc -l < /tmp/test35_lines.txt)" -eq 1 ] \
+    && grep -q '^{"schema_version":1,"kind":"session",' /tmp/test35_lines.txt; then
+    echo "✅ Test 35 passed - JSON documents and lines carry the schema version"
+else
+    echo "❌ Test 35 failed - JSON output malformed"
+    cat /tmp/test35_output.txt /tmp/test35_lines.txt
+fi
+
+# Test 36: Weekly and monthly reports compare with the previous period
+echo "📝 Test 36: Weekly and monthly reports"
+REPORT_TEST_DIR="/tmp/claude-diary-report-test"
+rm -rf "$REPORT_TEST_DIR"
+for session in "test-36a shop 2026-01-07 10" "test-36b blog 2026-01-08 30" "test-36c shop 2026-01-01 20" "test-36d shop 2026-01-12 20"; do
+  read -r id project day stop <<< "$session"
+  {
+    echo '{"hook_event_name": "UserPromptSubmit", "session_id": "'"$id"'", "cwd": "/tmp/'"$project"'", "prompt": "fix the login bug in auth.rs", "timestamp": "'"$day"'T12:00:00Z"}'
+    echo '{"hook_event_name": "PostToolUse", "session_id": "'"$id"'", "cwd": "/tmp/'"$project"'", "tool_name": "Edit", "tool_input": {"file_path": "/tmp/'"$project"'/auth.rs", "old_string": "a", "new_string": "b"}, "tool_response": {}, "timestamp": "'"$day"'T12:05:00Z"}'
+    echo '{"hook_event_name": "Stop", "session_id": "'"$id"'", "cwd": "/tmp/'"$project"'", "timestamp": "'"$day"'T12:'"$stop"':00Z"}'
+  } | $HOOK_BINARY --diary-dir "$REPORT_TEST_DIR" > /dev/null
+done
+$HOOK_BINARY --diary-dir "$REPORT_TEST_DIR" report --date 2026-01-07 > /tmp/test36_output.txt
+$HOOK_BINARY --diary-dir "$REPORT_TEST_DIR" report --month --date 2026-01-20 >> /tmp/test36_output.txt
+
+if grep -q "Weekly report: 2026-01-05 to 2026-01-11" /tmp/test36_output.txt && grep -q "\*\*Sessions\*\*: 2, +1 (+100%) vs previous" /tmp/test36_output.txt \
+    && grep -q "\*\*shop\*\*: ~10 minutes (66%)" /tmp/test36_output.txt && grep -q "Monthly report: 2026-01-01 to 2026-01-31" /tmp/test36_output.txt \
+    && grep -q "\*\*Sessions\*\*: 4, +4 vs previous" /tmp/test36_output.txt; then
+    echo "✅ Test 36 passed - Reports total the period and compare with the previous one"
+else
+    echo "❌ Test 36 failed - Report totals wrong"
+    cat /tmp/test36_output.txt
+fi
+
+# Test 37: The HTML report is one self-contained, escaped file
+echo "📝 Test 37: HTML report"
+HTML_TEST_DIR="/tmp/claude-diary-html-test"
+rm -rf "$HTML_TEST_DIR"
+{
+  echo '{"hook_event_name": "UserPromptSubmit", "session_id": "test-37", "cwd": "/tmp/shop", "prompt": "fix the login bug in auth.rs <script>alert(1)</script>", "timestamp": "2026-01-07T12:00:00Z"}'
+  echo '{"hook_event_name": "PostToolUse", "session_id": "test-37", "cwd": "/tmp/shop", "tool_name": "Edit", "tool_input": {"file_path": "/tmp/shop/auth.rs", "old_string": "a", "new_string": "b"}, "tool_response": {}, "timestamp": "2026-01-07T12:05:00Z"}'
+  echo '{"hook_event_name": "Stop", "session_id": "test-37", "cwd": "/tmp/shop", "timestamp": "2026-01-07T12:10:00Z"}'
+} | $HOOK_BINARY --diary-dir "$HTML_TEST_DIR" > /dev/null
+$HOOK_BINARY --diary-dir "$HTML_TEST_DIR" report --date 2026-01-07 --html "$HTML_TEST_DIR/week.html" > /tmp/test37_output.txt
+HTML_REPORT="$HTML_TEST_DIR/week.html"
+
+if [ -f "$HTML_REPORT" ] && grep -q "<svg" "$HTML_REPORT" && grep -q "<title>shop [0-9:]*–[0-9:]*" "$HTML_REPORT" \
+    && grep -q "&lt;script&gt;" "$HTML_REPORT" && ! grep -q "<script" "$HTML_REPORT" && ! grep -qE "(src|href)=\"https?:" "$HTML_REPORT"; then
+    echo "✅ Test 37 passed - HTML report written with charts and timeline"
+else
+    echo "❌ Test 37 failed - HTML report missing, unescaped or not self-contained"
+    cat /tmp/test37_output.txt
+fi
+
+# Test 38: Usage stats over a period and per project
+echo "📝 Test 38: Usage stats"
+STATS_TEST_DIR="/tmp/claude-diary-stats-test"
+rm -rf "$STATS_TEST_DIR"
+for session in "test-38a shop 2026-01-05" "test-38b shop 2026-01-06" "test-38c blog 2026-01-07" "test-38d shop 2026-01-09"; do
+  read -r id project day <<< "$session"
+  {
+    echo '{"hook_event_name": "UserPromptSubmit", "session_id": "'"$id"'", "cwd": "/tmp/'"$project"'", "prompt": "fix the login bug in auth.rs", "timestamp": "'"$day"'T12:00:00Z"}'
+    echo '{"hook_event_name": "PostToolUse", "session_id": "'"$id"'", "cwd": "/tmp/'"$project"'", "tool_name": "Edit", "tool_input": {"file_path": "/tmp/'"$project"'/auth.rs", "old_string": "a", "new_string": "b"}, "tool_response": {}, "timestamp": "'"$day"'T12:05:00Z"}'
+    echo '{"hook_event_name": "PostToolUse", "session_id": "'"$id"'", "cwd": "/tmp/'"$project"'", "tool_name": "Bash", "tool_input": {"command": "cargo build"}, "tool_response": {"is_error": true, "stderr": "error: could not compile"}, "timestamp": "'"$day"'T12:06:00Z"}'
+    echo '{"hook_event_name": "Stop", "session_id": "'"$id"'", "cwd": "/tmp/'"$project"'", "timestamp": "'"$day"'T12:10:00Z"}'
+  } | $HOOK_BINARY --diary-dir "$STATS_TEST_DIR" > /dev/null
+done
+$HOOK_BINARY --diary-dir "$STATS_TEST_DIR" stats --since 2026-01-01 --until 2026-01-31 > /tmp/test38_output.txt
+$HOOK_BINARY --diary-dir "$STATS_TEST_DIR" stats --since 2026-01-01 --until 2026-01-31 --project shop > /tmp/test38_project.txt
+
+if grep -q "\*\*Sessions\*\*: 4" /tmp/test38_output.txt && grep -q "(longest 3)" /tmp/test38_output.txt \
+    && grep -q "Week of 2026-01-05\*\*: 8 calls (Bash 4, Edit 4)" /tmp/test38_output.txt && grep -q "/tmp/shop/auth.rs: 3 edits in 3 sessions" /tmp/test38_output.txt \
+    && grep -q "Errors\*\*: 4" /tmp/test38_output.txt && grep -q "\*\*Sessions\*\*: 3" /tmp/test38_project.txt && ! grep -q "/tmp/blog" /tmp/test38_project.txt; then
+    echo "✅ Test 38 passed - Stats cover trends, files and streaks"
+else
+    echo "❌ Test 38 failed - Stats wrong"
+    cat /tmp/test38_output.txt /tmp/test38_project.txt
+fi
+
 # Cleanup
-rm -f /tmp/test*_output.txt /tmp/migration_output.txt
-rm -rf "$TEST_DIR" "$MIGRATION_TEST_DIR"
+rm -f /tmp/test*_output.txt /tmp/test14_*.txt /tmp/test16_again.txt /tmp/test18_*.txt /tmp/test19_today.txt /tmp/test31_transcript.jsonl /tmp/test35_lines.txt /tmp/test38_project.txt /tmp/migration_output.txt
+rm -rf "$TEST_DIR" "$MIGRATION_TEST_DIR" "$VAULT_TEST_DIR" "$RANGE_TEST_DIR" "$ICS_TEST_DIR" "$TIMESHEET_TEST_DIR" "$PAST_TEST_DIR" "$IDLE_TEST_DIR" "$GIT_TEST_DIR" "$STANDUP_TEST_DIR" "$TICKET_TEST_DIR" "$FILEREF_TEST_DIR" "$MCP_TEST_DIR" "$TECH_TEST_DIR" "$NARRATIVE_TEST_DIR" "$JSON_TEST_DIR" "$REPORT_TEST_DIR" "$HTML_TEST_DIR" "$STATS_TEST_DIR"
 
 echo ""
-echo "🎉 All 7 tests completed!"
+echo "🎉 All 38 tests completed!"
 echo ""
 echo "📖 Sample diary output:"
 echo "────────────────────────────────────────"